@@ -0,0 +1,115 @@
+use std::path::Path;
+use crate::config::Config;
+use crate::error::Error;
+use crate::input::{InputSettings, InputSource};
+use crate::runner::{format_duration, Outcome, Run};
+use crate::solution::Part;
+use crate::utils::format_table;
+
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+pub enum Verdict {
+    Pass,
+    Fail(String),
+    Missing
+}
+
+/// The known answers, in the configuration format with one section per puzzle and input, named
+/// `<year>.<day>/` followed by the file name for inputs read from files, or by the text itself for
+/// inline inputs, and holding `part_one` and `part_two`. Input files of the same name in other
+/// directories share their answers.
+pub struct Answers {
+    config: Config
+}
+
+impl Answers {
+    /// The answers file is given on the command line, named by `answers` in the configuration, or
+    /// the default file in the input directory.
+    pub fn read(path: Option<&str>, settings: &InputSettings) -> Result<Self, Error> {
+        let path = path
+            .or_else(|| settings.config.get("", "answers"))
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| Path::new(&settings.directory).join(DEFAULT_ANSWERS_FILE).to_string_lossy().into_owned());
+        Ok(Answers { config: Config::read(&path)? })
+    }
+
+    pub fn expected(&self, year: u32, day: u32, input: &InputSource, part: Part) -> Option<&str> {
+        let name = match input {
+            InputSource::File(path) => Path::new(path).file_name()?.to_string_lossy().into_owned(),
+            InputSource::Download(download) => Path::new(&download.path).file_name()?.to_string_lossy().into_owned(),
+            InputSource::Stdin => return None,
+            InputSource::Text(text) => text.clone()
+        };
+        let section = format!("{}.{}/{}", year, day, name);
+        let key = match part {
+            Part::One => "part_one",
+            Part::Two => "part_two"
+        };
+        self.config.get(&section, key)
+    }
+
+    /// A run without a known answer is missing unless it failed on the input it was given.
+    pub fn check(&self, run: &Run) -> Verdict {
+        let expected = run.input.as_ref()
+            .and_then(|input| self.expected(run.year, run.day, input, run.part));
+        match (&run.outcome, expected) {
+            (Outcome::Solved(answer), Some(expected)) if answer.to_string() == expected => Verdict::Pass,
+            (_, Some(expected)) => Verdict::Fail(expected.to_string()),
+            (Outcome::Failed(_), None) | (Outcome::Interrupted(_), None) if run.input.is_some() => {
+                Verdict::Fail(String::from("-"))
+            },
+            (_, None) => Verdict::Missing
+        }
+    }
+}
+
+/// Returns the table of runs with their expected answers, and whether none of them failed.
+pub fn format_checks(runs: &[Run], answers: &Answers) -> (String, bool) {
+    let mut passed = true;
+    let rows: Vec<Vec<String>> = runs.iter()
+        .map(|run| {
+            let answer = run.outcome.to_string();
+            let (expected, status) = match answers.check(run) {
+                Verdict::Pass => (answer.clone(), "pass"),
+                Verdict::Fail(expected) => {
+                    passed = false;
+                    (expected, "FAIL")
+                },
+                Verdict::Missing => (String::from("-"), "missing")
+            };
+            vec![
+                run.year.to_string(),
+                run.day.to_string(),
+                run.part.to_string(),
+                answer,
+                expected,
+                format_duration(run.duration),
+                String::from(status)
+            ]
+        })
+        .collect();
+    (format_table(&["year", "day", "part", "answer", "expected", "time", "status"], &rows), passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_answers() -> Answers {
+        let text = "[2017.13/input.txt]\npart_one = 24\n\n[2016.13/input.txt]\npart_one = 11\n\n[2016.14/abc]\npart_two = 22551\n";
+        Answers { config: Config::from_string(text, None).unwrap() }
+    }
+
+    #[test]
+    fn keeps_the_answers_of_each_puzzle_apart() {
+        let answers = get_answers();
+        let input = InputSource::File(String::from("inputs/input.txt"));
+        assert_eq!(answers.expected(2017, 13, &input, Part::One), Some("24"));
+        assert_eq!(answers.expected(2016, 13, &input, Part::One), Some("11"));
+        assert_eq!(answers.expected(2017, 12, &input, Part::One), None);
+        assert_eq!(answers.expected(2017, 13, &input, Part::Two), None);
+        let text = InputSource::Text(String::from("abc"));
+        assert_eq!(answers.expected(2016, 14, &text, Part::Two), Some("22551"));
+        assert_eq!(answers.expected(2016, 14, &InputSource::Stdin, Part::Two), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use crate::bench::Bench;
+use crate::config::Config;
+use crate::error::Error;
+use crate::input::InputSettings;
+use crate::runner::format_duration;
+use crate::solution::Part;
+use crate::utils::format_table;
+
+pub const DEFAULT_BASELINE_FILE: &str = "baseline.toml";
+
+/// How much slower than its baseline, in percent, a part may get before it counts as a regression.
+pub const DEFAULT_THRESHOLD: u32 = 10;
+
+/// Slowdowns shorter than this are timer noise on parts that only take microseconds, whatever the
+/// percentage.
+const NOISE: Duration = Duration::from_micros(100);
+
+const LATEST_KEY: &str = "latest";
+
+/// The short hash of the checked out git revision, if there is a git repository to ask.
+pub fn get_revision() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let revision = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if revision.is_empty() { None } else { Some(revision) }
+}
+
+/// Benchmark results saved for later runs to compare against, in the configuration format with
+/// one section per git revision holding the median parse plus solve time of each part in
+/// nanoseconds, keyed by `<year>.<day>.<part>`. The top-level `latest` key names the revision saved
+/// last.
+pub struct Baseline {
+    path: String,
+    config: Config
+}
+
+fn get_key(year: u32, day: u32, part: Part) -> String {
+    format!("{}.{}.{}", year, day, part)
+}
+
+fn get_time(bench: &Bench) -> Duration {
+    bench.parse.median + bench.solve.median
+}
+
+impl Baseline {
+    /// The baseline file is given on the command line, named by `baseline` in the configuration,
+    /// or the default file in the input directory.
+    pub fn read(path: Option<&str>, settings: &InputSettings) -> Result<Self, Error> {
+        let path = path
+            .or_else(|| settings.config.get("", "baseline"))
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| Path::new(&settings.directory).join(DEFAULT_BASELINE_FILE).to_string_lossy().into_owned());
+        let config = Config::read(&path)?;
+        Ok(Baseline { path, config })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get(&self, revision: &str, year: u32, day: u32, part: Part) -> Option<Duration> {
+        self.config.get(revision, &get_key(year, day, part))
+            .and_then(|nanos| nanos.parse::<u64>().ok())
+            .map(Duration::from_nanos)
+    }
+
+    /// The revision saved last, which runs compare against unless told otherwise.
+    pub fn latest(&self) -> Option<&str> {
+        self.config.get("", LATEST_KEY)
+    }
+
+    /// Replaces the times saved for the revision of the parts that were benchmarked, keeping the
+    /// other parts.
+    pub fn record(&mut self, revision: &str, benches: &[Bench]) {
+        for bench in benches {
+            let nanos = get_time(bench).as_nanos().to_string();
+            self.config.set(revision, &get_key(bench.year, bench.day, bench.part), &nanos);
+        }
+        self.config.set("", LATEST_KEY, revision);
+    }
+
+    pub fn write(&self) -> Result<(), Error> {
+        self.config.write(&self.path)
+    }
+}
+
+pub struct Comparison {
+    pub year: u32,
+    pub day: u32,
+    pub part: Part,
+    pub baseline: Option<Duration>,
+    pub current: Duration
+}
+
+impl Comparison {
+    /// How much slower the part got, in percent; negative when it got faster.
+    pub fn change(&self) -> Option<f64> {
+        let baseline = self.baseline?.as_secs_f64();
+        if baseline == 0.0 {
+            return None;
+        }
+        Some((self.current.as_secs_f64() / baseline - 1.0) * 100.0)
+    }
+
+    pub fn is_regression(&self, threshold: u32) -> bool {
+        match (self.baseline, self.change()) {
+            (Some(baseline), Some(change)) => change > threshold as f64 && self.current > baseline + NOISE,
+            _ => false
+        }
+    }
+}
+
+/// Pairs every benchmarked part with the time saved for it at the revision, if any.
+pub fn compare(benches: &[Bench], baseline: &Baseline, revision: &str) -> Vec<Comparison> {
+    benches.iter()
+        .map(|bench| Comparison {
+            year: bench.year,
+            day: bench.day,
+            part: bench.part,
+            baseline: baseline.get(revision, bench.year, bench.day, bench.part),
+            current: get_time(bench)
+        })
+        .collect()
+}
+
+/// Returns the table of parts with their baseline and current times, and whether none of them got
+/// slower than the threshold allows.
+pub fn format_comparisons(comparisons: &[Comparison], threshold: u32) -> (String, bool) {
+    let mut passed = true;
+    let rows: Vec<Vec<String>> = comparisons.iter()
+        .map(|comparison| {
+            let status = if comparison.is_regression(threshold) {
+                passed = false;
+                "REGRESSED"
+            } else if comparison.baseline.is_none() {
+                "new"
+            } else {
+                ""
+            };
+            vec![
+                comparison.year.to_string(),
+                comparison.day.to_string(),
+                comparison.part.to_string(),
+                comparison.baseline.map_or_else(|| String::from("-"), format_duration),
+                format_duration(comparison.current),
+                comparison.change().map_or_else(|| String::from("-"), |change| format!("{:+.1}%", change)),
+                String::from(status)
+            ]
+        })
+        .collect();
+    (format_table(&["year", "day", "part", "baseline", "current", "change", ""], &rows), passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::Statistics;
+
+    fn get_bench(day: u32, millis: u64) -> Bench {
+        let statistics = Statistics::from_samples(&[Duration::from_millis(millis)]).unwrap();
+        let parse = Statistics::from_samples(&[Duration::from_secs(0)]).unwrap();
+        Bench { year: 2017, day, part: Part::One, parse, solve: statistics }
+    }
+
+    #[test]
+    fn records_times_per_revision() {
+        let mut baseline = Baseline { path: String::new(), config: Config::new() };
+        baseline.record("abc1234", &[get_bench(14, 20)]);
+        let config = Config::from_string(&baseline.config.to_string(), None).unwrap();
+        let baseline = Baseline { path: String::new(), config };
+        assert_eq!(baseline.get("abc1234", 2017, 14, Part::One), Some(Duration::from_millis(20)));
+        assert_eq!(baseline.get("abc1234", 2017, 14, Part::Two), None);
+        assert_eq!(baseline.get("def5678", 2017, 14, Part::One), None);
+        assert_eq!(baseline.latest(), Some("abc1234"));
+    }
+
+    #[test]
+    fn remembers_the_revision_saved_last() {
+        let mut baseline = Baseline { path: String::new(), config: Config::new() };
+        assert_eq!(baseline.latest(), None);
+        baseline.record("def5678", &[get_bench(14, 20)]);
+        baseline.record("abc1234", &[get_bench(14, 25)]);
+        assert_eq!(baseline.latest(), Some("abc1234"));
+        let comparisons = compare(&[get_bench(14, 30)], &baseline, "def5678");
+        assert_eq!(comparisons[0].baseline, Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn flags_parts_slower_than_the_threshold() {
+        let mut baseline = Baseline { path: String::new(), config: Config::new() };
+        baseline.record("abc1234", &[get_bench(14, 20), get_bench(22, 20)]);
+        let benches = [get_bench(14, 21), get_bench(22, 30), get_bench(25, 5)];
+        let comparisons = compare(&benches, &baseline, "abc1234");
+        let regressions: Vec<bool> = comparisons.iter().map(|comparison| comparison.is_regression(10)).collect();
+        assert_eq!(regressions, vec![false, true, false]);
+        assert_eq!(comparisons[1].change().map(|change| change.round()), Some(50.0));
+        let (_, passed) = format_comparisons(&comparisons, 10);
+        assert!(!passed);
+    }
+
+    #[test]
+    fn ignores_noise_on_fast_parts() {
+        let comparison = Comparison {
+            year: 2017,
+            day: 14,
+            part: Part::One,
+            baseline: Some(Duration::from_micros(10)),
+            current: Duration::from_micros(50)
+        };
+        assert!(!comparison.is_regression(10));
+    }
+}
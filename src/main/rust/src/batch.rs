@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::registry::Puzzle;
+use crate::runner::{format_duration, run_jobs, run_part, Outcome, Run, RunOptions};
+use crate::solution::Part;
+use crate::utils::format_table;
+
+/// The files in the directory, sorted by name; subdirectories and hidden files are left out.
+pub fn get_input_files(directory: &str) -> Result<Vec<InputSource>, Error> {
+    let get_error = |source| Error::Io { path: directory.to_string(), source };
+    let mut paths: Vec<String> = Vec::new();
+    for entry in fs::read_dir(directory).map_err(get_error)? {
+        let entry = entry.map_err(get_error)?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.path().is_file() {
+            paths.push(entry.path().to_string_lossy().into_owned());
+        }
+    }
+    paths.sort();
+    Ok(paths.into_iter().map(InputSource::File).collect())
+}
+
+/// Runs both parts of one puzzle on every input, one after the other or each on its own thread.
+pub fn run_batch(puzzle: &Puzzle, inputs: &[InputSource], options: &RunOptions, parallel: bool) -> Vec<Run> {
+    let jobs: Vec<(&InputSource, Part)> = inputs.iter()
+        .flat_map(|input| vec![(input, Part::One), (input, Part::Two)])
+        .collect();
+    run_jobs(&jobs, parallel, |&(input, part)| run_part(puzzle, part, input, options))
+}
+
+fn get_file_name(run: &Run) -> String {
+    run.input.as_ref()
+        .and_then(|input| input.name())
+        .map(|path| Path::new(path).file_name().map_or(path.to_string(), |name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// The table of answers per file and part, where parts that failed, mostly on lines that don't
+/// parse in strict mode, and inputs with skipped lines stand out; the skipped lines of each file
+/// follow the table.
+pub fn format_batch(runs: &[Run]) -> String {
+    let rows: Vec<Vec<String>> = runs.iter()
+        .map(|run| {
+            let status = match &run.outcome {
+                Outcome::Failed(_) => String::from("FAILED"),
+                _ if !run.skipped.is_empty() => format!("{} skipped", run.skipped.len()),
+                _ => String::new()
+            };
+            vec![
+                get_file_name(run),
+                run.part.to_string(),
+                run.outcome.to_string(),
+                format_duration(run.duration),
+                status
+            ]
+        })
+        .collect();
+    let mut result = format_table(&["file", "part", "answer", "time", ""], &rows);
+    let mut reported: HashSet<String> = HashSet::new();
+    for run in runs.iter().filter(|run| !run.skipped.is_empty()) {
+        if reported.insert(get_file_name(run)) {
+            result.push('\n');
+            for skipped in &run.skipped {
+                result.push_str(&format!("\nskipped {}", skipped));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::ParseError;
+    use crate::solution::Answer;
+
+    fn get_run(file: &str, part: Part, outcome: Outcome, skipped: Vec<ParseError>) -> Run {
+        Run {
+            year: 2017,
+            day: 13,
+            part,
+            outcome,
+            duration: Duration::from_millis(1),
+            skipped,
+            warnings: Vec::new(),
+            input: Some(InputSource::File(format!("inputs/{}", file)))
+        }
+    }
+
+    #[test]
+    fn highlights_failures_and_skipped_lines_per_file() {
+        let skipped = || vec![ParseError::new(Some("inputs/b.txt"), 2, "0 3", "'<layer>: <depth>'")];
+        let runs = vec![
+            get_run("a.txt", Part::One, Outcome::Solved(Answer::Integer(24)), Vec::new()),
+            get_run("b.txt", Part::One, Outcome::Solved(Answer::Integer(0)), skipped()),
+            get_run("b.txt", Part::Two, Outcome::Solved(Answer::Integer(10)), skipped()),
+            get_run("c.txt", Part::One, Outcome::Failed(String::from("c.txt:1: expected a scanner")), Vec::new())
+        ];
+        let lines: Vec<String> = format_batch(&runs).lines().map(|line| line.trim_end().to_string()).collect();
+        assert_eq!(lines[2], "a.txt  1     24                                  1.000 ms");
+        assert_eq!(lines[3], "b.txt  1     0                                   1.000 ms  1 skipped");
+        assert_eq!(lines[5], "c.txt  1     error: c.txt:1: expected a scanner  1.000 ms  FAILED");
+        assert_eq!(&lines[6..], &["", "skipped inputs/b.txt:2: expected '<layer>: <depth>', found '0 3'"]);
+    }
+}
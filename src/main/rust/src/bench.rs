@@ -0,0 +1,144 @@
+use std::time::Duration;
+use crate::error::SolveError;
+use crate::input::InputSource;
+use crate::registry::Puzzle;
+use crate::runner::{format_duration, RunOptions};
+use crate::solution::Part;
+use crate::utils::{format_table, ParseContext};
+
+/// How many times each part runs: `warmup` runs that aren't measured, then `iterations` that are.
+pub struct BenchOptions {
+    pub warmup: usize,
+    pub iterations: usize
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions { warmup: 1, iterations: 10 }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Statistics {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration
+}
+
+impl Statistics {
+    /// The standard deviation is the sample one, zero for a single sample.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 1 {
+            sorted[middle]
+        } else {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        };
+        let seconds: Vec<f64> = sorted.iter().map(|duration| duration.as_secs_f64()).collect();
+        let mean = seconds.iter().sum::<f64>() / seconds.len() as f64;
+        let variance = if seconds.len() > 1 {
+            seconds.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (seconds.len() - 1) as f64
+        } else {
+            0.0
+        };
+        Some(Statistics {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt())
+        })
+    }
+}
+
+pub struct Bench {
+    pub year: u32,
+    pub day: u32,
+    pub part: Part,
+    pub parse: Statistics,
+    pub solve: Statistics
+}
+
+/// Reads the input once and times parsing and solving one part separately. Gives nothing for a
+/// part the puzzle doesn't solve, and an error when a run fails or is interrupted.
+pub fn bench_part(
+    puzzle: &Puzzle,
+    part: Part,
+    input: &InputSource,
+    options: &RunOptions,
+    bench_options: &BenchOptions
+) -> Result<Option<Bench>, String> {
+    let lines = input.read_lines().map_err(|error| error.to_string())?;
+    let mut parse_samples: Vec<Duration> = Vec::new();
+    let mut solve_samples: Vec<Duration> = Vec::new();
+    for iteration in 0..bench_options.warmup + bench_options.iterations {
+        let mut context = ParseContext::new(input.name(), options.strict);
+        let control = options.get_control(puzzle, part);
+        let timed = puzzle.solve_timed(&lines, part, &mut context, &control)
+            .map_err(|error| match error {
+                SolveError::Parse(error) => error.to_string(),
+                SolveError::NoAnswer(reason) => format!("part {} of puzzle {} {} has no answer: {}", part, puzzle.year, puzzle.day, reason),
+                SolveError::Interrupted(interrupted) => {
+                    format!("part {} of puzzle {} {} {}", part, puzzle.year, puzzle.day, interrupted)
+                }
+            })?;
+        if timed.answer.is_none() {
+            return Ok(None);
+        }
+        if iteration >= bench_options.warmup {
+            parse_samples.push(timed.parse);
+            solve_samples.push(timed.solve);
+        }
+    }
+    let parse = Statistics::from_samples(&parse_samples);
+    let solve = Statistics::from_samples(&solve_samples);
+    Ok(parse.zip(solve).map(|(parse, solve)| Bench { year: puzzle.year, day: puzzle.day, part, parse, solve }))
+}
+
+fn get_statistics_cells(statistics: &Statistics) -> Vec<String> {
+    [statistics.min, statistics.median, statistics.mean, statistics.stddev].iter()
+        .map(|&duration| format_duration(duration))
+        .collect()
+}
+
+pub fn format_benches(benches: &[Bench]) -> String {
+    let rows: Vec<Vec<String>> = benches.iter()
+        .map(|bench| {
+            let mut row = vec![bench.year.to_string(), bench.day.to_string(), bench.part.to_string()];
+            row.extend(get_statistics_cells(&bench.parse));
+            row.extend(get_statistics_cells(&bench.solve));
+            row
+        })
+        .collect();
+    let header = [
+        "year", "day", "part",
+        "parse min", "parse median", "parse mean", "parse stddev",
+        "solve min", "solve median", "solve mean", "solve stddev"
+    ];
+    format_table(&header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&value| Duration::from_millis(value)).collect()
+    }
+
+    #[test]
+    fn computes_statistics_of_samples() {
+        let statistics = Statistics::from_samples(&get_millis(&[4, 2, 8, 6])).unwrap();
+        assert_eq!(statistics.min, Duration::from_millis(2));
+        assert_eq!(statistics.median, Duration::from_millis(5));
+        assert_eq!((statistics.mean.as_secs_f64() * 1e6).round(), 5000.0);
+        assert_eq!((statistics.stddev.as_secs_f64() * 1e6).round(), 2582.0);
+        assert_eq!(Statistics::from_samples(&get_millis(&[3])).unwrap().stddev, Duration::from_secs(0));
+        assert_eq!(Statistics::from_samples(&[]), None);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::registry::{DefaultInput, Registry};
+use crate::utils::format_table;
+
+/// A value a solver takes besides its input, with the one the official puzzle asks for.
+pub struct Parameter {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub description: &'static str
+}
+
+/// What a solver module solves: the official year, day and title of the puzzle, where its input
+/// comes from by default and the parameters it takes. Module names follow the order the puzzles
+/// were solved in rather than the official numbering, so this is the only place that maps one to
+/// the other.
+pub struct Metadata {
+    pub year: u32,
+    pub day: u32,
+    pub title: &'static str,
+    pub module: &'static str,
+    pub input: DefaultInput,
+    pub parameters: &'static [Parameter]
+}
+
+pub const CATALOGUE: &[Metadata] = &[
+    Metadata {
+        year: 2016,
+        day: 10,
+        title: "Balance Bots",
+        module: "day_ten",
+        input: DefaultInput::File("day_ten.txt"),
+        parameters: &[
+            Parameter { name: "first_value", default: "17", description: "one of the chips the bot to find compares" },
+            Parameter { name: "second_value", default: "61", description: "the other chip the bot to find compares" }
+        ]
+    },
+    Metadata {
+        year: 2016,
+        day: 11,
+        title: "Radioisotope Thermoelectric Generators",
+        module: "day_eleven",
+        input: DefaultInput::None,
+        parameters: &[]
+    },
+    Metadata {
+        year: 2016,
+        day: 13,
+        title: "A Maze of Twisty Little Cubicles",
+        module: "day_twelve_2016",
+        input: DefaultInput::Text("1350"),
+        parameters: &[
+            Parameter { name: "start", default: "1,1", description: "the starting row and column" },
+            Parameter { name: "destination", default: "39,31", description: "the row and column to reach in part one" },
+            Parameter { name: "distance", default: "50", description: "the most steps taken in part two" }
+        ]
+    },
+    Metadata {
+        year: 2016,
+        day: 14,
+        title: "One-Time Pad",
+        module: "day_thirteen_2016",
+        input: DefaultInput::Text("cuanljph"),
+        parameters: &[
+            Parameter { name: "goal_num_keys", default: "64", description: "the key whose index is the answer" }
+        ]
+    },
+    Metadata {
+        year: 2016,
+        day: 15,
+        title: "Timing is Everything",
+        module: "day_fifteen_2016",
+        input: DefaultInput::File("day_fifteen_2016.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2016,
+        day: 16,
+        title: "Dragon Checksum",
+        module: "day_sixteen_2016",
+        input: DefaultInput::None,
+        parameters: &[
+            Parameter { name: "part_one_size", default: "272", description: "the disk size in part one" },
+            Parameter { name: "part_two_size", default: "35651584", description: "the disk size in part two" }
+        ]
+    },
+    Metadata {
+        year: 2016,
+        day: 20,
+        title: "Firewall Rules",
+        module: "day_twenty_2016",
+        input: DefaultInput::File("day_twenty_2016.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2016,
+        day: 22,
+        title: "Grid Computing",
+        module: "day_twentytwo_2016",
+        input: DefaultInput::File("day_twentytwo_2016.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 12,
+        title: "Digital Plumber",
+        module: "day_twelve",
+        input: DefaultInput::File("day_twelve.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 13,
+        title: "Packet Scanners",
+        module: "day_thirteen",
+        input: DefaultInput::File("day_thirteen.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 14,
+        title: "Disk Defragmentation",
+        module: "day_fourteen",
+        input: DefaultInput::Text("ugkiagan"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 16,
+        title: "Permutation Promenade",
+        module: "day_sixteen",
+        input: DefaultInput::File("day_sixteen.txt"),
+        parameters: &[
+            Parameter { name: "programs", default: "abcdefghijklmnop", description: "the programs in their starting order" }
+        ]
+    },
+    Metadata {
+        year: 2017,
+        day: 18,
+        title: "Duet",
+        module: "day_seventeen",
+        input: DefaultInput::File("day_eighteen.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 19,
+        title: "A Series of Tubes",
+        module: "day_nineteen",
+        input: DefaultInput::File("day_nineteen.txt"),
+        parameters: &[]
+    },
+    Metadata {
+        year: 2017,
+        day: 22,
+        title: "Sporifica Virus",
+        module: "day_twentytwo",
+        input: DefaultInput::File("day_twentytwo.txt"),
+        parameters: &[
+            Parameter { name: "part_one_bursts", default: "10000", description: "the bursts of activity in part one" },
+            Parameter { name: "part_two_bursts", default: "10000000", description: "the bursts of activity in part two" }
+        ]
+    }
+];
+
+pub fn find(year: u32, day: u32) -> Option<&'static Metadata> {
+    CATALOGUE.iter().find(|metadata| metadata.year == year && metadata.day == day)
+}
+
+pub fn find_module(module: &str) -> Option<&'static Metadata> {
+    CATALOGUE.iter().find(|metadata| metadata.module == module)
+}
+
+/// Lists every puzzle of the catalogue, telling which ones this build has solvers for.
+pub fn format_catalogue(registry: &Registry) -> String {
+    let rows: Vec<Vec<String>> = CATALOGUE.iter()
+        .map(|metadata| {
+            let input = match metadata.input {
+                DefaultInput::File(file) => file.to_string(),
+                DefaultInput::Text(text) => format!("\"{}\"", text),
+                DefaultInput::None => String::from("-")
+            };
+            let parameters: Vec<String> = metadata.parameters.iter()
+                .map(|parameter| format!("{}={}", parameter.name, parameter.default))
+                .collect();
+            let available = registry.find(metadata.year, metadata.day).is_some();
+            vec![
+                metadata.year.to_string(),
+                metadata.day.to_string(),
+                String::from(metadata.title),
+                String::from(metadata.module),
+                input,
+                parameters.join(" "),
+                String::from(if available { "" } else { "not built" })
+            ]
+        })
+        .collect();
+    format_table(&["year", "day", "title", "module", "input", "parameters", ""], &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::registry::get_registry;
+
+    #[test]
+    fn names_each_puzzle_and_module_once() {
+        let puzzles: HashSet<(u32, u32)> = CATALOGUE.iter().map(|metadata| (metadata.year, metadata.day)).collect();
+        let modules: HashSet<&str> = CATALOGUE.iter().map(|metadata| metadata.module).collect();
+        assert_eq!(puzzles.len(), CATALOGUE.len());
+        assert_eq!(modules.len(), CATALOGUE.len());
+    }
+
+    #[test]
+    fn registers_puzzles_by_their_official_day() {
+        assert_eq!(find_module("day_seventeen").map(|metadata| (metadata.year, metadata.day)), Some((2017, 18)));
+        assert_eq!(find(2016, 13).map(|metadata| metadata.module), Some("day_twelve_2016"));
+        for puzzle in get_registry().puzzles() {
+            assert_eq!(find(puzzle.year, puzzle.day).map(|metadata| metadata.module), Some(puzzle.name));
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::solution::Part;
+use crate::trace::trace;
+
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of saving checks between two looks at the clock.
+const CHECK_INTERVAL: u64 = 4096;
+
+/// The state of a long running loop, written as lines of text so that a run can go on from where
+/// an earlier one stopped. Reading gives `None` for lines that don't describe a state.
+pub trait Checkpoint: Sized {
+    fn to_lines(&self) -> Vec<String>;
+
+    fn from_lines(lines: &[String]) -> Option<Self>;
+}
+
+/// 64 bit FNV-1a, which unlike the standard library's hasher gives the same value on every build.
+fn get_hash(lines: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for line in lines {
+        for &byte in line.as_bytes().iter().chain(b"\n") {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Keeps the state of one part of one puzzle in a file of the checkpoint directory. The file
+/// starts with a key made of the puzzle, the part and a hash of the input, so that a run never
+/// goes on from the state of a run on another input.
+pub struct Checkpointer {
+    path: String,
+    key: String,
+    interval: Duration,
+    calls_left: Cell<u64>,
+    last_save: Cell<Instant>,
+    warnings: RefCell<Vec<String>>
+}
+
+impl Checkpointer {
+    pub fn new(directory: &str, year: u32, day: u32, part: Part, lines: &[String], interval: Duration) -> Self {
+        let file = format!("{}_day_{}_part_{}.checkpoint", year, day, part);
+        Checkpointer {
+            path: Path::new(directory).join(file).to_string_lossy().into_owned(),
+            key: format!("checkpoint {}.{}.{} {:016x}", year, day, part, get_hash(lines)),
+            interval,
+            calls_left: Cell::new(CHECK_INTERVAL),
+            last_save: Cell::new(Instant::now()),
+            warnings: RefCell::new(Vec::new())
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The saved state, if there is one for this puzzle, part and input.
+    pub fn restore<T: Checkpoint>(&self) -> Option<T> {
+        let string = fs::read_to_string(&self.path).ok()?;
+        let lines: Vec<String> = string.lines().map(|line| line.to_string()).collect();
+        if lines.first() != Some(&self.key) {
+            trace!(Info, "ignoring {}, which was saved for another run", self.path);
+            return None;
+        }
+        let state = T::from_lines(&lines[1..]);
+        match state {
+            Some(_) => trace!(Info, "resuming from {}", self.path),
+            None => trace!(Info, "ignoring {}, which doesn't hold a valid state", self.path)
+        }
+        state
+    }
+
+    /// Saves the state once the interval has gone by since the last save.
+    pub fn tick<T: Checkpoint>(&self, state: &T) {
+        let calls_left = self.calls_left.get() - 1;
+        if calls_left > 0 {
+            self.calls_left.set(calls_left);
+            return;
+        }
+        self.calls_left.set(CHECK_INTERVAL);
+        if self.last_save.get().elapsed() >= self.interval {
+            self.save(state);
+        }
+    }
+
+    /// Writes to a temporary file first, so that a run killed while saving keeps the last state.
+    /// A state that can't be saved only means a later run starts over, so failures are kept as
+    /// warnings rather than stopping the run; the same failure is kept once.
+    pub fn save<T: Checkpoint>(&self, state: &T) {
+        self.last_save.set(Instant::now());
+        let mut text = self.key.clone();
+        for line in state.to_lines() {
+            text.push('\n');
+            text.push_str(&line);
+        }
+        text.push('\n');
+        if let Err(error) = self.write(&text) {
+            let warning = format!("cannot write checkpoint '{}': {}", self.path, error);
+            let mut warnings = self.warnings.borrow_mut();
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+            return;
+        }
+        trace!(Info, "saved {}", self.path);
+    }
+
+    fn write(&self, text: &str) -> io::Result<()> {
+        if let Some(directory) = Path::new(&self.path).parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, text)?;
+        fs::rename(&temporary, &self.path)
+    }
+
+    /// The failures to save since the last call.
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
+    }
+
+    /// Removes the state once the part is solved.
+    pub fn clear(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+pub fn get_test_directory(name: &str) -> String {
+    let directory = std::env::temp_dir().join(format!("aoc_checkpoint_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    directory.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Counter(u64);
+
+    impl Checkpoint for Counter {
+        fn to_lines(&self) -> Vec<String> {
+            vec![self.0.to_string()]
+        }
+
+        fn from_lines(lines: &[String]) -> Option<Self> {
+            lines.first()?.parse::<u64>().ok().map(Counter)
+        }
+    }
+
+    fn get_lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn restores_states_saved_for_the_same_input() {
+        let directory = get_test_directory("restore");
+        let checkpointer = Checkpointer::new(&directory, 2017, 22, Part::Two, &get_lines(&["..#"]), DEFAULT_CHECKPOINT_INTERVAL);
+        assert_eq!(checkpointer.restore::<Counter>(), None);
+        checkpointer.save(&Counter(42));
+        assert_eq!(checkpointer.restore::<Counter>(), Some(Counter(42)));
+        let other = Checkpointer::new(&directory, 2017, 22, Part::Two, &get_lines(&["#.."]), DEFAULT_CHECKPOINT_INTERVAL);
+        assert_eq!(other.restore::<Counter>(), None);
+        checkpointer.clear();
+        assert_eq!(checkpointer.restore::<Counter>(), None);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn saves_once_the_interval_has_gone_by() {
+        let directory = get_test_directory("interval");
+        let checkpointer = Checkpointer::new(&directory, 2016, 14, Part::Two, &[], Duration::from_secs(0));
+        for count in 0..CHECK_INTERVAL - 1 {
+            checkpointer.tick(&Counter(count));
+        }
+        assert!(!Path::new(checkpointer.path()).exists());
+        checkpointer.tick(&Counter(7));
+        assert_eq!(checkpointer.restore::<Counter>(), Some(Counter(7)));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn keeps_failures_to_save_as_warnings() {
+        let directory = get_test_directory("warnings");
+        fs::write(&directory, "not a directory").unwrap();
+        let checkpointer = Checkpointer::new(&directory, 2017, 22, Part::Two, &[], DEFAULT_CHECKPOINT_INTERVAL);
+        checkpointer.save(&Counter(1));
+        checkpointer.save(&Counter(2));
+        let warnings = checkpointer.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with(&format!("cannot write checkpoint '{}'", checkpointer.path())), "{:?}", warnings);
+        assert!(checkpointer.take_warnings().is_empty());
+        fs::remove_file(&directory).unwrap();
+    }
+}
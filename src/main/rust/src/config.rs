@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use crate::error::{Error, ParseError};
+use crate::utils::get_lines;
+
+/// A small subset of TOML: `[section]` headers followed by `key = value` lines, where values are
+/// either bare words or double quoted strings, and `#` starts a comment. Quoted strings escape
+/// `\`, `"` and newlines with a backslash.
+#[derive(Default)]
+pub struct Config {
+    sections: HashMap<String, HashMap<String, String>>
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Config { sections: HashMap::new() }
+    }
+
+    pub fn from_string(string: &str, file: Option<&str>) -> Result<Self, ParseError> {
+        let mut config = Config::new();
+        let mut section = String::new();
+        for (index, line) in get_lines(string).iter().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+            let (key, value) = parse_entry(line)
+                .ok_or_else(|| ParseError::new(file, index + 1, line, "'[section]' or 'key = value'"))?;
+            config.set(&section, &key, &value);
+        }
+        Ok(config)
+    }
+
+    /// Reads the file at `path`; a missing file is an empty configuration.
+    pub fn read(path: &str) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(string) => Ok(Config::from_string(&string, Some(path))?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Config::new()),
+            Err(source) => Err(Error::Io { path: path.to_string(), source })
+        }
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), Error> {
+        fs::write(path, self.to_string())
+            .map_err(|source| Error::Write { path: path.to_string(), source })
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(section)
+            .and_then(|entries| entries.get(key))
+            .map(|value| value.as_str())
+    }
+
+    pub fn entries(&self, section: &str) -> Vec<(&str, &str)> {
+        self.sections.get(section)
+            .map(|entries| entries.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.sections.entry(section.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+/// Writes sections and keys in sorted order, so that the same configuration always reads the same;
+/// comments of a configuration that was read aren't kept.
+impl fmt::Display for Config {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut sections: Vec<&String> = self.sections.keys().collect();
+        sections.sort();
+        for (index, section) in sections.iter().enumerate() {
+            if index > 0 {
+                writeln!(formatter)?;
+            }
+            if !section.is_empty() {
+                writeln!(formatter, "[{}]", section)?;
+            }
+            let entries = &self.sections[*section];
+            let mut keys: Vec<&String> = entries.keys().collect();
+            keys.sort();
+            for key in keys {
+                let value = entries[key].replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+                writeln!(formatter, "{} = \"{}\"", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, chr) in line.char_indices() {
+        match chr {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..index],
+            _ => ()
+        }
+    }
+    line
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(chr) = chars.next() {
+        if chr != '\\' {
+            result.push(chr);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\')
+        }
+    }
+    result
+}
+
+fn parse_entry(line: &str) -> Option<(String, String)> {
+    let mut split = line.splitn(2, '=');
+    let key = split.next()?.trim();
+    let value = split.next()?.trim();
+    if key.is_empty() {
+        return None;
+    }
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        unescape(&value[1..value.len() - 1])
+    } else {
+        value.to_string()
+    };
+    Some((key.to_string(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_values_that_read_back_the_same() {
+        let mut config = Config::new();
+        config.set("2017.18", "input", "set a \"b\\\" # c\\n\nsnd a");
+        config.set("", "answer", "C:\\\\inputs");
+        let read = Config::from_string(&config.to_string(), None).unwrap();
+        assert_eq!(read.get("2017.18", "input"), config.get("2017.18", "input"));
+        assert_eq!(read.get("", "answer"), Some("C:\\\\inputs"));
+        assert_eq!(Config::from_string("key = \"a \\\" # b\" # comment", None).unwrap().get("", "key"), Some("a \" # b"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::cell::Cell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::checkpoint::{Checkpoint, Checkpointer};
+use crate::error::{Interrupted, StopReason};
+
+/// Number of calls to `Control::check` between two looks at the token, the clock and the callback,
+/// so that loops can check on every step.
+const CHECK_INTERVAL: u64 = 256;
+
+/// Shortest time between two progress reports.
+const REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Cancels the runs holding a clone of it, from any thread.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Handed to the solvers so that long loops stop when the run is cancelled or out of time, and
+/// report how many steps they got through. Loops that keep their state in a `Checkpoint` can also
+/// save it as they go and when they stop, and go on from the saved state in a later run.
+pub struct Control<'a> {
+    token: CancelToken,
+    deadline: Option<Instant>,
+    progress: Option<Box<dyn Fn(u64) + 'a>>,
+    checkpointer: Option<Checkpointer>,
+    calls_left: Cell<u64>,
+    last_report: Cell<Instant>
+}
+
+impl<'a> Control<'a> {
+    /// A control that never stops the run.
+    pub fn new() -> Self {
+        Control::with_token(CancelToken::new())
+    }
+
+    pub fn with_token(token: CancelToken) -> Self {
+        Control {
+            token,
+            deadline: None,
+            progress: None,
+            checkpointer: None,
+            calls_left: Cell::new(CHECK_INTERVAL),
+            last_report: Cell::new(Instant::now())
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn progress<F: Fn(u64) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn checkpoint(mut self, checkpointer: Checkpointer) -> Self {
+        self.checkpointer = Some(checkpointer);
+        self
+    }
+
+    /// The state saved by an earlier run of the same part on the same input, if checkpoints are on.
+    pub fn restore<T: Checkpoint>(&self) -> Option<T> {
+        self.checkpointer.as_ref().and_then(|checkpointer| checkpointer.restore())
+    }
+
+    /// Checks like `check`, also saving the state every now and then and when the loop has to stop.
+    pub fn check_saving<T: Checkpoint>(&self, progress: u64, state: &T) -> Result<(), Interrupted> {
+        let result = self.check(progress);
+        if let Some(checkpointer) = &self.checkpointer {
+            match result {
+                Ok(()) => checkpointer.tick(state),
+                Err(_) => checkpointer.save(state)
+            }
+        }
+        result
+    }
+
+    /// What went wrong without stopping the run, such as checkpoints that couldn't be saved.
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.checkpointer.as_ref().map_or_else(Vec::new, |checkpointer| checkpointer.take_warnings())
+    }
+
+    /// Forgets the saved state once the part is solved.
+    pub fn clear_checkpoint(&self) {
+        if let Some(checkpointer) = &self.checkpointer {
+            checkpointer.clear();
+        }
+    }
+
+    /// Called by the solvers with the number of steps done so far, and returns an error telling how
+    /// far they got once they should stop.
+    pub fn check(&self, progress: u64) -> Result<(), Interrupted> {
+        let calls_left = self.calls_left.get() - 1;
+        if calls_left > 0 {
+            self.calls_left.set(calls_left);
+            return Ok(());
+        }
+        self.calls_left.set(CHECK_INTERVAL);
+        if self.token.is_cancelled() {
+            return Err(Interrupted { reason: StopReason::Cancelled, progress });
+        }
+        let now = Instant::now();
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            return Err(Interrupted { reason: StopReason::TimedOut, progress });
+        }
+        if let Some(callback) = &self.progress {
+            if now - self.last_report.get() >= REPORT_INTERVAL {
+                self.last_report.set(now);
+                callback(progress);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Default for Control<'a> {
+    fn default() -> Self {
+        Control::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_steps(control: &Control, steps: u64) -> Result<(), Interrupted> {
+        for step in 0..steps {
+            control.check(step)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stops_once_cancelled() {
+        let token = CancelToken::new();
+        let control = Control::with_token(token.clone());
+        assert!(run_steps(&control, 1000).is_ok());
+        token.cancel();
+        let interrupted = run_steps(&control, 1000).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::Cancelled);
+    }
+
+    #[test]
+    fn stops_after_the_timeout() {
+        let control = Control::new().timeout(Duration::from_secs(0));
+        let interrupted = run_steps(&control, 1000).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::TimedOut);
+        assert_eq!(interrupted.progress, CHECK_INTERVAL - 1);
+    }
+}
@@ -0,0 +1,210 @@
+use std::panic::{self, AssertUnwindSafe};
+use crate::input::InputSettings;
+use crate::random::Random;
+use crate::registry::Registry;
+use crate::utils::format_table;
+
+/// Answers the same question about an input with a slow reference algorithm and with the fast one
+/// the solver uses, returning both answers, or `None` when the input can't be compared.
+pub type Compare = fn(&[String]) -> Option<(String, String)>;
+
+pub struct CrossCheck {
+    pub name: &'static str,
+    pub year: u32,
+    pub day: u32,
+    compare: Compare
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    Answers { reference: String, fast: String },
+    Panic(String)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Skipped,
+    Agreed,
+    Disagreed(Mismatch)
+}
+
+pub struct Disagreement {
+    pub source: String,
+    pub input: Vec<String>,
+    pub mismatch: Mismatch
+}
+
+pub struct Report {
+    pub name: &'static str,
+    pub compared: usize,
+    pub skipped: usize,
+    pub disagreements: Vec<Disagreement>
+}
+
+fn get_panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload.downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("panic"))
+}
+
+impl CrossCheck {
+    pub fn new(name: &'static str, year: u32, day: u32, compare: Compare) -> Self {
+        CrossCheck { name, year, day, compare }
+    }
+
+    /// A panic in either algorithm counts as a disagreement.
+    pub fn compare(&self, lines: &[String]) -> Comparison {
+        match panic::catch_unwind(AssertUnwindSafe(|| (self.compare)(lines))) {
+            Ok(None) => Comparison::Skipped,
+            Ok(Some((reference, fast))) if reference == fast => Comparison::Agreed,
+            Ok(Some((reference, fast))) => Comparison::Disagreed(Mismatch::Answers { reference, fast }),
+            Err(payload) => Comparison::Disagreed(Mismatch::Panic(get_panic_message(payload)))
+        }
+    }
+
+    /// Removes chunks of lines, halving the chunks down to single lines, for as long as the two
+    /// algorithms still disagree on what is left.
+    pub fn minimize(&self, lines: &[String]) -> Vec<String> {
+        let mut current = lines.to_vec();
+        let mut chunk = std::cmp::max(current.len() / 2, 1);
+        loop {
+            let mut removed = false;
+            let mut start: usize = 0;
+            while start < current.len() && current.len() > 1 {
+                let end = std::cmp::min(start + chunk, current.len());
+                let candidate: Vec<String> = current[..start].iter()
+                    .chain(current[end..].iter())
+                    .cloned()
+                    .collect();
+                if !candidate.is_empty() && matches!(self.compare(&candidate), Comparison::Disagreed(_)) {
+                    current = candidate;
+                    removed = true;
+                } else {
+                    start = end;
+                }
+            }
+            if !removed {
+                if chunk == 1 {
+                    return current;
+                }
+                chunk /= 2;
+            }
+        }
+    }
+
+    /// Compares the algorithms on every input, given with a description of where it comes from,
+    /// and shrinks the inputs they disagree on. Panics are expected here, so the panic messages
+    /// aren't printed while it runs.
+    pub fn run(&self, inputs: &[(String, Vec<String>)]) -> Report {
+        let mut report = Report { name: self.name, compared: 0, skipped: 0, disagreements: Vec::new() };
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        for (source, lines) in inputs {
+            match self.compare(lines) {
+                Comparison::Skipped => report.skipped += 1,
+                Comparison::Agreed => report.compared += 1,
+                Comparison::Disagreed(mismatch) => {
+                    report.compared += 1;
+                    let input = self.minimize(lines);
+                    let mismatch = match self.compare(&input) {
+                        Comparison::Disagreed(minimized) => minimized,
+                        _ => mismatch
+                    };
+                    report.disagreements.push(Disagreement { source: source.clone(), input, mismatch });
+                }
+            }
+        }
+        panic::set_hook(hook);
+        report
+    }
+}
+
+pub fn get_cross_checks() -> Vec<CrossCheck> {
+    #[allow(unused_mut)]
+    let mut checks: Vec<CrossCheck> = Vec::new();
+    #[cfg(feature = "year2017")]
+    {
+        crate::day_thirteen::register_cross_checks(&mut checks);
+        crate::day_fourteen::register_cross_checks(&mut checks);
+    }
+    checks
+}
+
+/// The puzzle's own input when it can be read, followed by `num_seeds` generated inputs.
+pub fn get_inputs(registry: &Registry, settings: &InputSettings, check: &CrossCheck, num_seeds: u64) -> Vec<(String, Vec<String>)> {
+    let mut inputs: Vec<(String, Vec<String>)> = Vec::new();
+    if let Some(puzzle) = registry.find(check.year, check.day) {
+        if let Some(input) = settings.resolve(puzzle) {
+            if let Ok(lines) = input.read_lines() {
+                inputs.push((input.name().unwrap_or("puzzle input").to_string(), lines));
+            }
+        }
+        for seed in 0..num_seeds {
+            if let Some(lines) = puzzle.generate(&mut Random::new(seed)) {
+                inputs.push((format!("seed {}", seed), lines));
+            }
+        }
+    }
+    inputs
+}
+
+pub fn format_reports(reports: &[Report]) -> String {
+    let rows: Vec<Vec<String>> = reports.iter()
+        .map(|report| {
+            vec![
+                String::from(report.name),
+                report.compared.to_string(),
+                report.skipped.to_string(),
+                report.disagreements.len().to_string()
+            ]
+        })
+        .collect();
+    let mut result = format_table(&["check", "compared", "skipped", "disagreements"], &rows);
+    for report in reports {
+        for disagreement in &report.disagreements {
+            let mismatch = match &disagreement.mismatch {
+                Mismatch::Answers { reference, fast } => format!("reference {}, fast {}", reference, fast),
+                Mismatch::Panic(message) => format!("panicked: {}", message)
+            };
+            result.push_str(&format!("\n\n{} on {}: {}\n", report.name, disagreement.source, mismatch));
+            result.push_str(&disagreement.input.join("\n"));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare_sums(lines: &[String]) -> Option<(String, String)> {
+        let numbers: Vec<i64> = lines.iter().map(|line| line.parse::<i64>()).collect::<Result<_, _>>().ok()?;
+        let reference: i64 = numbers.iter().sum();
+        let fast: i64 = numbers.iter().filter(|&&number| number != 13).sum();
+        assert!(!numbers.contains(&0), "zero");
+        Some((reference.to_string(), fast.to_string()))
+    }
+
+    fn get_lines(numbers: &[&str]) -> Vec<String> {
+        numbers.iter().map(|number| number.to_string()).collect()
+    }
+
+    #[test]
+    fn compares_answers() {
+        let check = CrossCheck::new("sums", 0, 0, compare_sums);
+        assert_eq!(check.compare(&get_lines(&["1", "2"])), Comparison::Agreed);
+        assert_eq!(check.compare(&get_lines(&["one"])), Comparison::Skipped);
+        let mismatch = Mismatch::Answers { reference: String::from("14"), fast: String::from("1") };
+        assert_eq!(check.compare(&get_lines(&["1", "13"])), Comparison::Disagreed(mismatch));
+    }
+
+    #[test]
+    fn shrinks_inputs_to_the_lines_that_disagree() {
+        let check = CrossCheck::new("sums", 0, 0, compare_sums);
+        let report = check.run(&[(String::from("test"), get_lines(&["1", "2", "13", "4", "5", "6", "0", "8"]))]);
+        assert_eq!(report.disagreements.len(), 1);
+        let input = &report.disagreements[0].input;
+        assert!(input == &get_lines(&["13"]) || input == &get_lines(&["0"]), "{:?}", input);
+    }
+}
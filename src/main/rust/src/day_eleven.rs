@@ -1,29 +1,110 @@
 use crate::utils::remove;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 use std::collections::HashSet;
 use queues::*;
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
-enum Element {
+pub enum Element {
     Generator(u64),
     Microchip(u64)
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-struct State {
+/// An item of a floor, by the name of its element.
+enum Item {
+    Generator(String),
+    Microchip(String)
+}
+
+impl Item {
+    fn from_string(string: &str) -> Result<Self, PatternError> {
+        parse_line!(string, {
+            r"^an? (\w+) generator$" => |name: String| Item::Generator(name),
+            r"^an? (\w+)-compatible microchip$" => |name: String| Item::Microchip(name)
+        })
+    }
+}
+
+/// Splits `a, b, and c` or `a and b` into its items.
+fn get_items(contents: &str) -> Vec<&str> {
+    if contents == "nothing relevant" {
+        return Vec::new();
+    }
+    contents.split(", ")
+        .flat_map(|item| item.split(" and "))
+        .map(|item| item.trim_start_matches("and "))
+        .collect()
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Default)]
+pub struct State {
     elevator: usize,
     floors: [Vec<Element>; 4]
 }
 
 impl State {
 
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             elevator: 0,
             floors: [vec![], vec![], vec![], vec![]]
         }
     }
 
-    fn place(&mut self, floor: usize, elements: &[Element]) {
+    pub fn from_strings(strings: &[String], context: &mut ParseContext) -> Result<Self, ParseError> {
+        let mut state = State::new();
+        let mut names: Vec<String> = Vec::new();
+        for (index, string) in strings.iter().enumerate() {
+            if string.trim().is_empty() {
+                continue;
+            }
+            let floor = parse_line!(string, r"^The (first|second|third|fourth) floor contains (.+?)\.?$" => |ordinal: String, contents: String| {
+                let floor = match ordinal.as_str() {
+                    "first" => 0,
+                    "second" => 1,
+                    "third" => 2,
+                    _ => 3
+                };
+                (floor, contents)
+            });
+            let (floor, contents) = match context.accept(index + 1, string, "'The <first|second|third|fourth> floor contains ...'", floor)? {
+                Some(floor) => floor,
+                None => continue
+            };
+            let mut elements: Vec<Element> = Vec::new();
+            for item in get_items(&contents) {
+                let expected = "'a <element> generator' or 'a <element>-compatible microchip'";
+                let item = match context.accept(index + 1, string, expected, Item::from_string(item))? {
+                    Some(item) => item,
+                    None => continue
+                };
+                let name = match &item {
+                    Item::Generator(name) | Item::Microchip(name) => name
+                };
+                let id = match names.iter().position(|other| other == name) {
+                    Some(index) => index as u64,
+                    None => {
+                        names.push(name.clone());
+                        (names.len() - 1) as u64
+                    }
+                };
+                elements.push(match item {
+                    Item::Generator(_) => Element::Generator(id),
+                    Item::Microchip(_) => Element::Microchip(id)
+                });
+            }
+            state.place(floor, &elements);
+        }
+        Ok(state)
+    }
+
+    pub fn place(&mut self, floor: usize, elements: &[Element]) {
         for &element in elements {
             self.floors[floor].push(element);
         }
@@ -52,7 +133,7 @@ impl State {
 
     fn is_microchip_safe(microchip_id: u64, elements: &[Element]) -> bool {
         let has_generators = elements.iter()
-            .filter(|&element| if let Element::Generator(_) = element { true } else { false })
+            .filter(|element| matches!(element, Element::Generator(_)))
             .count() > 0;
         if !has_generators {
             true
@@ -105,7 +186,7 @@ fn get_new_states(state: &State, states: &HashSet<State>) -> Vec<State> {
     let mut result: Vec<State> = Vec::new();
     for &elevator_offset in &[-1i64, 1i64] {
         let signed_updated_elevator = (state.elevator as i64) + elevator_offset;
-        if signed_updated_elevator >= 0 && signed_updated_elevator < 4 {
+        if (0..4).contains(&signed_updated_elevator) {
             let updated_elevator = signed_updated_elevator as usize;
             for &element in &state.floors[state.elevator] {
                 if state.is_move_safe(updated_elevator, Some(element), None) {
@@ -117,12 +198,10 @@ fn get_new_states(state: &State, states: &HashSet<State>) -> Vec<State> {
             }
             for (first_index, &first_element) in state.floors[state.elevator].iter().enumerate() {
                 for (second_index, &second_element) in state.floors[state.elevator].iter().enumerate() {
-                    if first_index != second_index {
-                        if state.is_move_safe(updated_elevator, Some(first_element), Some(second_element)) {
-                            let updated_state = state.get_updated(updated_elevator, Some(first_element), Some(second_element));
-                            if !states.contains(&updated_state) {
-                                result.push(updated_state);
-                            }
+                    if first_index != second_index && state.is_move_safe(updated_elevator, Some(first_element), Some(second_element)) {
+                        let updated_state = state.get_updated(updated_elevator, Some(first_element), Some(second_element));
+                        if !states.contains(&updated_state) {
+                            result.push(updated_state);
                         }
                     }
                 }
@@ -132,28 +211,135 @@ fn get_new_states(state: &State, states: &HashSet<State>) -> Vec<State> {
     result
 }
 
-fn get_min_steps(state: &State) -> u64 {
+/// Gives `None` when the items can't all be brought up, like when the elevator has nothing to
+/// carry on its floor.
+pub fn get_min_steps(state: &State, control: &Control) -> Result<Option<u64>, Interrupted> {
     let mut states: HashSet<State> = HashSet::new();
     states.insert(state.clone());
     let mut queue: Queue<(State, u64)> = Queue::new();
-    queue.add((state.clone(), 0));
-    while let Some((current_state, steps)) = queue.remove().ok() {
+    let _ = queue.add((state.clone(), 0));
+    while let Ok((current_state, steps)) = queue.remove() {
+        control.check(states.len() as u64)?;
         if current_state.is_goal() {
-            return steps;
+            trace!(Info, "reached the goal in {} steps after seeing {} states", steps, states.len());
+            return Ok(Some(steps));
         }
         let new_states = get_new_states(&current_state, &states);
+        trace!(Trace, "expanding a state {} steps away on floor {}: {} new states", steps, current_state.elevator, new_states.len());
         for new_state in new_states {
-            queue.add((new_state.clone(), steps + 1));
+            let _ = queue.add((new_state.clone(), steps + 1));
             states.insert(new_state.clone());
         }
     }
-    0
+    Ok(None)
+}
+
+const ELEMENT_NAMES: [&str; 5] = ["hydrogen", "lithium", "strontium", "plutonium", "thulium"];
+const FLOOR_NAMES: [&str; 4] = ["first", "second", "third", "fourth"];
+
+/// At most three elements, since the search grows quickly with every pair of items. The first
+/// generator starts on the first floor, where the elevator needs something to carry.
+pub fn generate_floors(random: &mut Random) -> Vec<String> {
+    let num_elements = random.range(1, 4) as usize;
+    let mut floors: Vec<Vec<String>> = vec![vec![]; 4];
+    for (index, &name) in ELEMENT_NAMES.iter().take(num_elements).enumerate() {
+        let generator_floor = if index == 0 { 0 } else { random.range(0, 4) as usize };
+        floors[generator_floor].push(format!("a {} generator", name));
+        floors[random.range(0, 4) as usize].push(format!("a {}-compatible microchip", name));
+    }
+    floors.iter()
+        .zip(FLOOR_NAMES.iter())
+        .map(|(items, floor_name)| {
+            let contents = match items.len() {
+                0 => String::from("nothing relevant"),
+                1 => items[0].clone(),
+                2 => format!("{} and {}", items[0], items[1]),
+                length => format!("{}, and {}", items[..length - 1].join(", "), items[length - 1])
+            };
+            format!("The {} floor contains {}.", floor_name, contents)
+        })
+        .collect()
+}
+
+pub struct DayEleven;
+
+impl Solution for DayEleven {
+    type Input = State;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        State::from_strings(lines, context)
+    }
+
+    fn part_one(&self, state: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        get_min_steps(state, control)?
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::NoAnswer(String::from("the items can't all be brought to the fourth floor")))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_floors(random))
+    }
 }
 
-pub fn test() {
-    let mut state = State::new();
-    state.place(0, &[Element::Microchip(0), Element::Microchip(1)]);
-    state.place(1, &[Element::Generator(0)]);
-    state.place(2, &[Element::Generator(1)]);
-    println!("{}", get_min_steps(&state));
-}
\ No newline at end of file
+pub fn register(registry: &mut Registry) {
+    registry.register("day_eleven", DayEleven);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::StopReason;
+    use crate::solution::{assert_generated_inputs_parse, get_example_parse_error, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.",
+        "The second floor contains a hydrogen generator.",
+        "The third floor contains a lithium generator.",
+        "The fourth floor contains nothing relevant."
+    ];
+
+    #[test]
+    fn moves_the_example_items_in_eleven_steps() {
+        assert_eq!(solve_example(&DayEleven, EXAMPLE, Part::One), Some(Answer::Integer(11)));
+    }
+
+    #[test]
+    fn places_the_example_items_on_their_floors() {
+        let lines: Vec<String> = EXAMPLE.iter().map(|line| line.to_string()).collect();
+        let state = State::from_strings(&lines, &mut ParseContext::new(None, true)).unwrap();
+        let mut expected = State::new();
+        expected.place(0, &[Element::Microchip(0), Element::Microchip(1)]);
+        expected.place(1, &[Element::Generator(0)]);
+        expected.place(2, &[Element::Generator(1)]);
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn rejects_items_that_are_neither_generators_nor_microchips() {
+        let error = get_example_parse_error(&DayEleven, &["The first floor contains a lithium generator, a banana, and a lithium-compatible microchip."]);
+        assert_eq!(error.line, 1);
+        assert_eq!(error.expected, "'a <element> generator' or 'a <element>-compatible microchip'");
+    }
+
+    #[test]
+    fn reports_items_the_elevator_cannot_reach() {
+        let mut state = State::new();
+        state.place(1, &[Element::Generator(0), Element::Microchip(0)]);
+        assert_eq!(get_min_steps(&state, &Control::new()), Ok(None));
+    }
+
+    #[test]
+    fn stops_searching_when_out_of_time() {
+        let mut state = State::new();
+        let elements: Vec<Element> = (0..5).flat_map(|element| vec![Element::Generator(element), Element::Microchip(element)]).collect();
+        state.place(0, &elements);
+        let interrupted = get_min_steps(&state, &Control::new().timeout(Duration::from_secs(0))).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::TimedOut);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayEleven);
+    }
+}
@@ -1,23 +1,24 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
 #[derive(Clone, Copy)]
-struct Disk {
+pub struct Disk {
     position: u64,
     num_positions: u64
 }
 
 impl Disk {
 
-    fn from_string(string: &str) -> Option<Self> {
-        lazy_static! {
-            static ref REGEX: Regex = Regex::new(r"^Disc #\d+ has (\d+) positions; at time=0, it is at position (\d+)\.$").unwrap();
-        }
-        let captures = REGEX.captures(string)?;
-        let num_positions = captures.get(1)?.as_str().parse::<u64>().ok()?;
-        let position = captures.get(2)?.as_str().parse::<u64>().ok()?;
-        Some(Disk::new(num_positions, position))
+    fn from_string(string: &str) -> Result<Self, PatternError> {
+        parse_line!(string, r"^Disc #\d+ has (\d+) positions; at time=0, it is at position (\d+)\.$" => |num_positions: u64, position: u64| {
+            Disk::new(num_positions, position)
+        })
     }
 
     fn new(num_positions: u64, position: u64) -> Self {
@@ -40,28 +41,99 @@ fn is_passing_at_time(disks: &[Disk], time: u64) -> bool {
     true
 }
 
-fn get_fall_time(disks: &[Disk]) -> u64 {
+pub fn get_fall_time(disks: &[Disk], control: &Control) -> Result<u64, Interrupted> {
     let mut fall_time: u64 = 0;
     while !is_passing_at_time(disks, fall_time) {
+        control.check(fall_time)?;
         fall_time += 1;
     }
-    fall_time
+    trace!(Info, "the capsule falls through {} disks at time {}", disks.len(), fall_time);
+    Ok(fall_time)
+}
+
+pub fn parse_disks(lines: &[String], context: &mut ParseContext) -> Result<Vec<Disk>, ParseError> {
+    context.parse_lines(
+        lines,
+        "'Disc #<n> has <n> positions; at time=0, it is at position <n>.'",
+        Disk::from_string
+    )
+}
+
+/// Disk sizes are distinct primes, so that the capsule always falls through at some point.
+pub fn generate_disks(random: &mut Random) -> Vec<String> {
+    let mut sizes = [2, 3, 5, 7, 11, 13, 17, 19];
+    random.shuffle(&mut sizes);
+    let num_disks = random.range(1, 7) as usize;
+    sizes[..num_disks].iter()
+        .enumerate()
+        .map(|(index, &num_positions)| {
+            let disk = index + 1;
+            let position = random.range(0, num_positions);
+            format!("Disc #{} has {} positions; at time=0, it is at position {}.", disk, num_positions, position)
+        })
+        .collect()
+}
+
+pub struct DayFifteen2016;
+
+impl Solution for DayFifteen2016 {
+    type Input = Vec<Disk>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_disks(lines, context)
+    }
+
+    fn part_one(&self, disks: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_fall_time(disks, control)?))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_disks(random))
+    }
 }
 
-fn read_disks(path: &str) -> Vec<Disk> {
-    read_lines(path)
-        .map_or(
-            vec![], 
-            |strings| {
-                strings.iter()
-                    .filter_map(|string| Disk::from_string(string))
-                    .collect()
-            }
-        )
+pub fn register(registry: &mut Registry) {
+    registry.register("day_fifteen_2016", DayFifteen2016);
 }
 
-pub fn solve_part_one() {
-    let disks = read_disks("day_fifteen_2016.txt");
-    let answer = get_fall_time(&disks);
-    println!("{}", answer);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::StopReason;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    #[test]
+    fn finds_the_example_fall_time() {
+        let example = &[
+            "Disc #1 has 5 positions; at time=0, it is at position 4.",
+            "Disc #2 has 2 positions; at time=0, it is at position 1."
+        ];
+        assert_eq!(solve_example(&DayFifteen2016, example, Part::One), Some(Answer::Integer(5)));
+    }
+
+    #[test]
+    fn gives_up_on_disks_that_never_line_up() {
+        let disks = vec![Disk::new(2, 0), Disk::new(2, 0)];
+        let control = Control::new().timeout(Duration::from_millis(10));
+        let interrupted = get_fall_time(&disks, &control).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::TimedOut);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayFifteen2016);
+    }
+
+    #[test]
+    fn generates_disks_of_distinct_sizes() {
+        for seed in 0..20 {
+            let lines = generate_disks(&mut Random::new(seed));
+            let disks = parse_disks(&lines, &mut ParseContext::new(None, true)).unwrap();
+            let mut sizes: Vec<u64> = disks.iter().map(|disk| disk.num_positions).collect();
+            sizes.sort();
+            sizes.dedup();
+            assert_eq!(sizes.len(), disks.len(), "{:?}", lines);
+        }
+    }
+}
@@ -1,4 +1,12 @@
 use crate::matrix::Matrix;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::crosscheck::CrossCheck;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
 use queues::*;
 use std::collections::HashSet;
 
@@ -37,9 +45,7 @@ fn reverse_buffer(buffer: &mut [u8], start: usize, length: usize) {
         } else {
             buffer_size + end_index - offset
         };
-        let temp = buffer[first_index];
-        buffer[first_index] = buffer[second_index];
-        buffer[second_index] = temp;
+        buffer.swap(first_index, second_index);
     }
 }
 
@@ -68,18 +74,25 @@ fn get_sparse_hash(buffer: &[u8], lengths: &[usize]) -> Vec<u8> {
 
 fn get_dense_hash(buffer: &[u8]) -> Vec<u8> {
     buffer.chunks(16)
-        .map(|chunk| chunk.iter().map(|&x| x).reduce(|acc, x| acc ^ x).unwrap_or(0))
+        .map(|chunk| chunk.iter().copied().reduce(|acc, x| acc ^ x).unwrap_or(0))
         .collect()
 }
 
-fn get_hash(input: &[usize]) -> Vec<u8> {
+pub fn get_hash(input: &[usize]) -> Vec<u8> {
     let buffer: Vec<u8> = (0..=255).collect();
-    let mut lengths: Vec<usize> = input.iter().map(|x| *x).collect();
+    let mut lengths: Vec<usize> = input.to_vec();
     lengths.extend_from_slice(&SUFIX);
     let sparse_hash = get_sparse_hash(&buffer, &lengths);
     get_dense_hash(&sparse_hash)
 }
 
+pub fn get_knot_hash(string: &str) -> String {
+    let input: Vec<usize> = string.as_bytes().iter().map(|&x| x as usize).collect();
+    get_hash(&input).iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 fn get_bits(n: u8) -> Vec<bool> {
     let mut result: Vec<bool> = Vec::new();
     for shift in (0..8).rev() {
@@ -108,13 +121,12 @@ fn get_buffer_num_set_bits(buffer: &[u8]) -> u64 {
 
 fn get_buffer_bits(buffer: &[u8]) -> Vec<bool> {
     buffer.iter()
-        .map(|&x| get_bits(x))
-        .flatten()
+        .flat_map(|&x| get_bits(x))
         .collect()
 }
 
 
-fn get_row_used_cells(key: &str, row: u64) -> u64 {
+pub fn get_row_used_cells(key: &str, row: u64) -> u64 {
     let mut hash_input_str = String::from(key);
     hash_input_str.push('-');
     hash_input_str.push_str(&row.to_string());
@@ -123,7 +135,7 @@ fn get_row_used_cells(key: &str, row: u64) -> u64 {
     get_buffer_num_set_bits(&hash)
 }
 
-fn get_row(key: &str, row: u64) -> Vec<bool> {
+pub fn get_row(key: &str, row: u64) -> Vec<bool> {
     let mut hash_input_str = String::from(key);
     hash_input_str.push('-');
     hash_input_str.push_str(&row.to_string());
@@ -132,7 +144,7 @@ fn get_row(key: &str, row: u64) -> Vec<bool> {
     get_buffer_bits(&hash)
 }
 
-fn get_disk(key: &str) -> Matrix<bool> {
+pub fn get_disk(key: &str) -> Matrix<bool> {
     let mut matrix: Matrix<bool> = Matrix::new(128, 128, false);
     for row in 0..128 {
         let row_data = get_row(key, row);
@@ -145,7 +157,7 @@ fn get_disk(key: &str) -> Matrix<bool> {
 
 fn get_connected_component(matrix: &Matrix<bool>, start: (usize, usize)) -> HashSet<(usize, usize)> {
     let mut queue: Queue<(usize, usize)> = Queue::new();
-    queue.add(start);
+    let _ = queue.add(start);
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
     visited.insert(start);
     while let Ok((row, col)) = queue.remove() {
@@ -157,7 +169,7 @@ fn get_connected_component(matrix: &Matrix<bool>, start: (usize, usize)) -> Hash
                 let updated_col = offseted_col as usize;
                 if matrix.get(updated_row, updated_col) && !visited.contains(&(updated_row, updated_col)) {
                     visited.insert((updated_row, updated_col));
-                    queue.add((updated_row, updated_col));
+                    let _ = queue.add((updated_row, updated_col));
                 }
             }
         }
@@ -165,14 +177,15 @@ fn get_connected_component(matrix: &Matrix<bool>, start: (usize, usize)) -> Hash
     visited
 }
 
-fn get_num_connected_components(matrix: &Matrix<bool>) -> u64 {
+pub fn get_num_connected_components(matrix: &Matrix<bool>) -> u64 {
     let mut num_connected_components: u64 = 0;
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
     for row in 0..matrix.rows {
         for col in 0..matrix.cols {
             if matrix.get(row, col) && !visited.contains(&(row, col)) {
                 num_connected_components += 1;
-                let coordinates = get_connected_component(&matrix, (row, col));
+                let coordinates = get_connected_component(matrix, (row, col));
+                trace!(Debug, "region {} at ({}, {}) has {} squares", num_connected_components, row, col, coordinates.len());
                 for &coordinate in &coordinates {
                     visited.insert(coordinate);
                 }
@@ -182,15 +195,83 @@ fn get_num_connected_components(matrix: &Matrix<bool>) -> u64 {
     num_connected_components
 }
 
-fn get_used_cells(key: &str) -> u64 {
+pub fn get_used_cells(key: &str) -> u64 {
     (0..128).map(|row| get_row_used_cells(key, row)).sum()
 }
 
-pub fn solve_part_one() {
-    println!("{}", get_used_cells("ugkiagan"))
+/// Counts the used squares of the whole disk, as a reference for `get_used_cells`.
+fn cross_check_used_cells(lines: &[String]) -> Option<(String, String)> {
+    let key = lines.first()?;
+    let disk = get_disk(key);
+    let reference = (0..disk.rows)
+        .flat_map(|row| (0..disk.cols).map(move |col| (row, col)))
+        .filter(|&(row, col)| disk.get(row, col))
+        .count();
+    Some((reference.to_string(), get_used_cells(key).to_string()))
+}
+
+pub fn generate_key(random: &mut Random) -> Vec<String> {
+    vec![random.word(8)]
+}
+
+pub struct DayFourteen;
+
+impl Solution for DayFourteen {
+    type Input = String;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        context.parse_first(lines, "a key string", |line| Some(line.to_string()))
+    }
+
+    fn part_one(&self, key: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_used_cells(key)))
+    }
+
+    fn part_two(&self, key: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        let disk = get_disk(key);
+        Ok(Some(Answer::from(get_num_connected_components(&disk))))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_key(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_fourteen", DayFourteen);
+}
+
+pub fn register_cross_checks(checks: &mut Vec<CrossCheck>) {
+    checks.push(CrossCheck::new("2017 14 used squares", 2017, 14, cross_check_used_cells));
 }
 
-pub fn solve_part_two() {
-    let disk = get_disk("ugkiagan");
-    println!("{}", get_num_connected_components(&disk));
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &["flqrgnkx"];
+
+    #[test]
+    fn hashes_the_knot_hash_examples() {
+        assert_eq!(get_knot_hash(""), "a2582a3a0e66e6e86e3812dcb672a272");
+        assert_eq!(get_knot_hash("AoC 2017"), "33efeb34ea91902bb2f59c9920caa6cd");
+        assert_eq!(get_knot_hash("1,2,3"), "3efbe78a8d82f29979031a4aa0b16a9d");
+        assert_eq!(get_knot_hash("1,2,4"), "63960835bcdc130f0b66d7ff4f6a5a8e");
+    }
+
+    #[test]
+    fn counts_the_used_squares_of_the_example() {
+        assert_eq!(solve_example(&DayFourteen, EXAMPLE, Part::One), Some(Answer::Integer(8108)));
+    }
+
+    #[test]
+    fn counts_the_regions_of_the_example() {
+        assert_eq!(solve_example(&DayFourteen, EXAMPLE, Part::Two), Some(Answer::Integer(1242)));
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayFourteen);
+    }
+}
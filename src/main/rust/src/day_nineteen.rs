@@ -1,5 +1,12 @@
+use std::collections::HashSet;
 use crate::matrix::Matrix;
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
 
 static NORTH: (isize, isize) = (-1, 0);
 static EAST: (isize, isize) = (0, 1);
@@ -18,7 +25,7 @@ fn get_oposite(direction: (isize, isize)) -> (isize, isize) {
 }
 
 #[derive(Clone, Copy)]
-enum Tile {
+pub enum Tile {
     Empty,
     Path,
     PathWithId(char)
@@ -34,9 +41,9 @@ impl Tile {
     }
 }
 
-fn get_tile_map(strings: &[String]) -> Matrix<Tile> {
+pub fn get_tile_map(strings: &[String]) -> Matrix<Tile> {
     let rows = strings.len();
-    let cols = strings.first().map(|string| string.len()).unwrap_or(0);
+    let cols = strings.iter().map(|string| string.chars().count()).max().unwrap_or(0);
     let mut tile_map: Matrix<Tile> = Matrix::new(rows, cols, Tile::Empty);
     for (row, string) in strings.iter().enumerate() {
         for (col, char) in string.chars().enumerate() {
@@ -59,26 +66,11 @@ struct Packet {
 }
 
 impl Packet {
-    fn get_initial_packet(tile_map: &Matrix<Tile>) -> Self {
-        for row in 0..tile_map.rows {
-            for col in 0..tile_map.cols {
-                if tile_map.get(row, col).is_path() {
-                    let (row_offset, col_offset) = EAST;
-                    let row_offseted = row as isize + row_offset;
-                    let col_offseted = col as isize + col_offset;
-                    if tile_map.has_coordinate(row_offseted, col_offseted) && tile_map.get(row_offseted as usize, col_offseted as usize).is_path() {
-                        return Packet { row, col, direction: EAST };
-                    }
-                    let (row_offset, col_offset) = SOUTH;
-                    let row_offseted = row as isize + row_offset;
-                    let col_offseted = col as isize + col_offset;
-                    if tile_map.has_coordinate(row_offseted, col_offseted) && tile_map.get(row_offseted as usize, col_offseted as usize).is_path() {
-                        return Packet { row, col, direction: SOUTH };
-                    }
-                }
-            }
-        }
-        panic!("");
+    /// The packet enters going down through the first row.
+    fn get_initial_packet(tile_map: &Matrix<Tile>) -> Option<Self> {
+        (0..tile_map.cols)
+            .find(|&col| tile_map.rows > 0 && tile_map.get(0, col).is_path())
+            .map(|col| Packet { row: 0, col, direction: SOUTH })
     }
     
     fn update(&mut self, tile_map: &Matrix<Tile>) -> bool {
@@ -101,6 +93,7 @@ impl Packet {
                         self.row = row_offseted as usize;
                         self.col = col_offseted as usize;
                         self.direction = direction;
+                        trace!(Debug, "turns to {:?} at ({}, {})", direction, self.row, self.col);
                         break;
                     }
                 }
@@ -114,29 +107,178 @@ impl Packet {
     }
 }
 
-fn get_packet_trail(tile_map: &Matrix<Tile>) -> Vec<(usize, usize)> {
+/// Follows the path until it ends; a path that loops back on itself runs until `control` stops it.
+fn get_packet_trail(tile_map: &Matrix<Tile>, control: &Control) -> Result<Vec<(usize, usize)>, Interrupted> {
     let mut coordinates: Vec<(usize, usize)> = Vec::new();
-    let mut packet = Packet::get_initial_packet(tile_map);
+    let mut packet = match Packet::get_initial_packet(tile_map) {
+        Some(packet) => packet,
+        None => return Ok(coordinates)
+    };
     coordinates.push(packet.get_coordinates());
     while packet.update(tile_map) {
+        control.check(coordinates.len() as u64)?;
         coordinates.push(packet.get_coordinates());
     }
-    coordinates
+    Ok(coordinates)
 }
 
-fn get_packet_ids_trail(tile_map: &Matrix<Tile>) -> String {
+pub fn get_packet_ids_trail(tile_map: &Matrix<Tile>, control: &Control) -> Result<String, Interrupted> {
     let mut result = String::from("");
-    let coordinates = get_packet_trail(tile_map);
+    let coordinates = get_packet_trail(tile_map, control)?;
     for (row, col) in coordinates {
         if let Tile::PathWithId(chr) = tile_map.get(row, col) {
+            trace!(Debug, "sees {} at ({}, {})", chr, row, col);
             result.push(chr);
         }
     }
-    result
+    Ok(result)
+}
+
+/// Walks a path on every other row and column, so that two parts of the path are never next to
+/// each other and the packet can't take a wrong turn. The path doesn't cross itself.
+pub fn generate_diagram(random: &mut Random) -> Vec<String> {
+    let nodes_rows = random.range(2, 12) as isize;
+    let nodes_cols = random.range(2, 12) as isize;
+    let rows = (2 * nodes_rows) as usize;
+    let cols = (2 * nodes_cols - 1) as usize;
+    let mut diagram: Vec<Vec<char>> = vec![vec![' '; cols]; rows];
+    let mut letters: Vec<char> = ('A'..='Z').collect();
+    random.shuffle(&mut letters);
+    let mut visited: HashSet<(isize, isize)> = HashSet::new();
+    let mut node = (0, random.range(0, nodes_cols as u64) as isize);
+    let mut direction = SOUTH;
+    diagram[0][(2 * node.1) as usize] = '|';
+    visited.insert(node);
+    loop {
+        let mut directions: Vec<(isize, isize)> = DIRECTIONS.iter()
+            .copied()
+            .filter(|&other| other != get_oposite(direction))
+            .filter(|&(row_offset, col_offset)| {
+                let next = (node.0 + row_offset, node.1 + col_offset);
+                next.0 >= 0 && next.0 < nodes_rows && next.1 >= 0 && next.1 < nodes_cols && !visited.contains(&next)
+            })
+            .collect();
+        let (row, col) = ((2 * node.0 + 1) as usize, (2 * node.1) as usize);
+        if directions.is_empty() || random.one_in(40) {
+            diagram[row][col] = letters.pop().unwrap_or('|');
+            break;
+        }
+        random.shuffle(&mut directions);
+        let next_direction = if directions.contains(&direction) && !random.one_in(3) { direction } else { directions[0] };
+        let line = if next_direction.0 == 0 { '-' } else { '|' };
+        diagram[row][col] = if next_direction != direction { '+' } else { line };
+        let connector = &mut diagram[(row as isize + next_direction.0) as usize][(col as isize + next_direction.1) as usize];
+        *connector = if random.one_in(4) { letters.pop().unwrap_or(line) } else { line };
+        direction = next_direction;
+        node = (node.0 + direction.0, node.1 + direction.1);
+        visited.insert(node);
+    }
+    diagram.iter().map(|row| row.iter().collect()).collect()
 }
 
-pub fn solve_part_one() {
-    let strings = read_lines("day_nineteen.txt").unwrap_or(vec![]);
-    let tile_map = get_tile_map(&strings);
-    println!("{}", get_packet_ids_trail(&tile_map));
-}
\ No newline at end of file
+fn is_diagram_char(chr: char) -> bool {
+    matches!(chr, ' ' | '|' | '-' | '+' | 'A'..='Z')
+}
+
+/// Trailing empty lines are dropped. The rows have to make a rectangle with the packet's entry on
+/// the first row, which is always checked since the packet walks the diagram by coordinates; other
+/// characters are read as empty space unless parsing is strict.
+pub fn parse_diagram(lines: &[String], context: &mut ParseContext) -> Result<Matrix<Tile>, ParseError> {
+    let end = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |index| index + 1);
+    let rows = &lines[..end];
+    let first = rows.first().ok_or_else(|| context.error(1, "", "a diagram"))?;
+    let num_entries = first.matches('|').count();
+    if num_entries != 1 {
+        let detail = format!("it has {}", num_entries);
+        return Err(context.error(1, first, "one '|' on the first row, where the packet enters").with_detail(Some(detail)));
+    }
+    let width = first.chars().count();
+    for (index, line) in rows.iter().enumerate() {
+        if line.chars().count() != width {
+            let detail = format!("the first row has {} characters", width);
+            return Err(context.error(index + 1, line, "rows of the same length").with_detail(Some(detail)));
+        }
+        let checked = match line.chars().find(|&chr| !is_diagram_char(chr)) {
+            Some(chr) => Err(format!("'{}' is not part of a path", chr)),
+            None => Ok(())
+        };
+        context.accept(index + 1, line, "spaces, '|', '-', '+' and capital letters", checked)?;
+    }
+    Ok(get_tile_map(rows))
+}
+
+pub struct DayNineteen;
+
+impl Solution for DayNineteen {
+    type Input = Matrix<Tile>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_diagram(lines, context)
+    }
+
+    fn part_one(&self, tile_map: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_packet_ids_trail(tile_map, control)?))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_diagram(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_nineteen", DayNineteen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::StopReason;
+    use crate::solution::{assert_generated_inputs_parse, get_example_parse_error, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "     |          ",
+        "     |  +--+    ",
+        "     A  |  C    ",
+        " F---|----E|--+ ",
+        "     |  |  |  D ",
+        "     +B-+  +--+ "
+    ];
+
+    #[test]
+    fn collects_the_letters_along_the_example_path() {
+        assert_eq!(solve_example(&DayNineteen, EXAMPLE, Part::One), Some(Answer::Text(String::from("ABCDEF"))));
+    }
+
+    #[test]
+    fn rejects_diagrams_it_cannot_walk() {
+        let error = get_example_parse_error(&DayNineteen, &["abc"]);
+        assert_eq!(error.to_string(), "<input>:1: expected one '|' on the first row, where the packet enters, found 'abc' (it has 0)");
+        let error = get_example_parse_error(&DayNineteen, &[" | ", " |"]);
+        assert_eq!(error.to_string(), "<input>:2: expected rows of the same length, found ' |' (the first row has 3 characters)");
+        let error = get_example_parse_error(&DayNineteen, &[" | ", " a "]);
+        assert_eq!(error.to_string(), "<input>:2: expected spaces, '|', '-', '+' and capital letters, found ' a ' ('a' is not part of a path)");
+    }
+
+    #[test]
+    fn reads_unknown_characters_as_space_unless_strict() {
+        let lines: Vec<String> = [" | ", " A ", " * ", ""].iter().map(|line| line.to_string()).collect();
+        let mut context = ParseContext::new(None, false);
+        let tile_map = parse_diagram(&lines, &mut context).unwrap();
+        assert_eq!(context.skipped.len(), 1);
+        assert_eq!(get_packet_ids_trail(&tile_map, &Control::new()), Ok(String::from("A")));
+    }
+
+    #[test]
+    fn stops_following_a_path_that_loops() {
+        let lines: Vec<String> = [" |  ", "+|-+", "+--|", " +-+"].iter().map(|line| line.to_string()).collect();
+        let control = Control::new().timeout(Duration::from_secs(0));
+        let interrupted = get_packet_ids_trail(&get_tile_map(&lines), &control).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::TimedOut);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayNineteen);
+    }
+}
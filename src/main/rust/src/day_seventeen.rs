@@ -1,18 +1,31 @@
 use std::collections::HashMap;
-use lazy_static::lazy_static;
-use regex::Regex;
-use crate::utils::read_lines;
-use std::sync::mpsc;
-use std::thread;
+use std::str::FromStr;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::panic;
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often a process waiting for a value looks whether it should stop.
+const RECEIVE_INTERVAL: Duration = Duration::from_millis(10);
 
 #[derive(Copy, Clone)]
-enum Operand {
+pub enum Operand {
     Value(i64),
     Register(u8)
 }
 
 #[derive(Copy, Clone)]
-enum Instruction {
+pub enum Instruction {
     Sound { operand: Operand },
     Set { dest: u8, value: Operand },
     Add { dest: u8, value: Operand },
@@ -22,94 +35,57 @@ enum Instruction {
     Jump { test: Operand, offset: Operand }
 }
 
-impl Instruction {
-    fn get_operand(string: &str) -> Operand {
-        if let Some(value) = string.parse::<i64>().ok() {
-            Operand::Value(value)
-        } else {
-            Operand::Register(string.as_bytes()[0])
-        }
-    }
+impl FromStr for Operand {
+    type Err = ();
 
-    fn get_sound_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"snd ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let capture = RE.captures(string)?.get(1)?.as_str();
-        Some(Instruction::Sound { operand: Self::get_operand(capture) })
-    }
-
-    fn get_set_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"set (\w) ((?:[-+]?\d+)|\w)").unwrap();
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if let Ok(value) = string.parse::<i64>() {
+            Ok(Operand::Value(value))
+        } else if string.len() == 1 && string.as_bytes()[0].is_ascii_lowercase() {
+            Ok(Operand::Register(string.as_bytes()[0]))
+        } else {
+            Err(())
         }
-        let register = RE.captures(string)?.get(1)?.as_str().as_bytes()[0];
-        let second_capture = RE.captures(string)?.get(2)?.as_str();
-        let value = Self::get_operand(second_capture);
-        Some(Instruction::Set { dest: register, value })
     }
+}
 
-    fn get_add_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"add (\w) ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let register = RE.captures(string)?.get(1)?.as_str().as_bytes()[0];
-        let second_capture = RE.captures(string)?.get(2)?.as_str();
-        let value = Self::get_operand(second_capture);
-        Some(Instruction::Add { dest: register, value })
+impl Instruction {
+    pub fn get_instruction(string: &str) -> Result<Instruction, PatternError> {
+        parse_line!(string, {
+            r"snd ((?:[-+]?\d+)|\w)" => |operand: Operand| Instruction::Sound { operand },
+            r"set (\w) ((?:[-+]?\d+)|\w)" => |dest: char, value: Operand| Instruction::Set { dest: dest as u8, value },
+            r"add (\w) ((?:[-+]?\d+)|\w)" => |dest: char, value: Operand| Instruction::Add { dest: dest as u8, value },
+            r"mul (\w) ((?:[-+]?\d+)|\w)" => |dest: char, value: Operand| Instruction::Mul { dest: dest as u8, value },
+            r"mod (\w) ((?:[-+]?\d+)|\w)" => |dest: char, value: Operand| Instruction::Mod { dest: dest as u8, value },
+            r"rcv ((?:[-+]?\d+)|\w)" => |operand: Operand| Instruction::Recover { operand },
+            r"jgz (\w) ((?:[-+]?\d+)|\w)" => |test: Operand, offset: Operand| Instruction::Jump { test, offset }
+        })
     }
+}
 
-    fn get_mul_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"mul (\w) ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let register = RE.captures(string)?.get(1)?.as_str().as_bytes()[0];
-        let second_capture = RE.captures(string)?.get(2)?.as_str();
-        let value = Self::get_operand(second_capture);
-        Some(Instruction::Mul { dest: register, value })
-    }
-
-    fn get_mod_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"mod (\w) ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let register = RE.captures(string)?.get(1)?.as_str().as_bytes()[0];
-        let second_capture = RE.captures(string)?.get(2)?.as_str();
-        let value = Self::get_operand(second_capture);
-        Some(Instruction::Mod { dest: register, value })
+/// The value `add`, `mul` or `mod` leave in their register, or `None` for other instructions and
+/// when the result overflows or the divisor is zero.
+fn get_arithmetic_result(instruction: Instruction, register: i64, operand: i64) -> Option<i64> {
+    match instruction {
+        Instruction::Add { .. } => register.checked_add(operand),
+        Instruction::Mul { .. } => register.checked_mul(operand),
+        Instruction::Mod { .. } => register.checked_rem(operand),
+        _ => None
     }
+}
 
-    fn get_recover_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"rcv ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let capture = RE.captures(string)?.get(1)?.as_str();
-        Some(Instruction::Recover { operand: Self::get_operand(capture) })
-    }
+fn get_arithmetic_error(instruction_pointer: usize) -> String {
+    format!("instruction {} overflows or divides by zero", instruction_pointer + 1)
+}
 
-    fn get_jump_instruction(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"jgz (\w) ((?:[-+]?\d+)|\w)").unwrap();
-        }
-        let first_capture = RE.captures(string)?.get(1)?.as_str();
-        let second_capture = RE.captures(string)?.get(2)?.as_str();
-        let test = Self::get_operand(first_capture);
-        let offset = Self::get_operand(second_capture);
-        Some(Instruction::Jump { test, offset })
-    }
-
-    fn get_instruction(string: &str) -> Option<Instruction> {
-        Self::get_sound_instruction(string)
-            .or(Self::get_set_instruction(string))
-            .or(Self::get_add_instruction(string))
-            .or(Self::get_mul_instruction(string))
-            .or(Self::get_mod_instruction(string))
-            .or(Self::get_recover_instruction(string))
-            .or(Self::get_jump_instruction(string))
-    }
+/// The instruction a jump lands on; jumps out of the program, however far, end up past its end.
+fn get_jump_target(instruction_pointer: usize, offset: i64) -> usize {
+    (instruction_pointer as i64).checked_add(offset)
+        .and_then(|target| usize::try_from(target).ok())
+        .unwrap_or(usize::MAX)
 }
 
-struct Computer {
+pub struct Computer {
     instruction_pointer: usize,
     instructions: Vec<Instruction>,
     registers: HashMap<u8, i64>,
@@ -118,12 +94,12 @@ struct Computer {
 }
 
 impl Computer {
-    fn new(instructions: &[Instruction]) -> Self {
+    pub fn new(instructions: &[Instruction]) -> Self {
         let mut registers: HashMap<u8, i64> = HashMap::new();
         for chr in b'a'..=b'z' {
             registers.insert(chr, 0);
         }
-        let instructions_vec: Vec<_> = instructions.iter().map(|&instr| instr).collect();
+        let instructions_vec = instructions.to_vec();
         Computer {
             instruction_pointer: 0,
             instructions: instructions_vec,
@@ -150,73 +126,78 @@ impl Computer {
         self.registers.get(&dest).map_or(0, |&value| value)
     }
 
-    fn execute(&mut self) {
+    /// Returns `false` once a jump left the program, which ends it, and an error when arithmetic
+    /// overflows.
+    pub fn execute(&mut self) -> Result<bool, String> {
+        let instruction = match self.instructions.get(self.instruction_pointer) {
+            Some(&instruction) => instruction,
+            None => return Ok(false)
+        };
         let mut has_jumped = false;
-        match self.instructions[self.instruction_pointer] {
-            Instruction::Sound { operand } => self.last_sound = Some(self.get_operand_value(operand)),
+        match instruction {
+            Instruction::Sound { operand } => {
+                let sound = self.get_operand_value(operand);
+                trace!(Debug, "plays {} at {}", sound, self.instruction_pointer);
+                self.last_sound = Some(sound);
+            },
             Instruction::Set { dest, value: operation_value } => {
                 let value = self.get_operand_value(operation_value);
                 self.set_register(dest, value);
             },
-            Instruction::Add { dest, value: operation_value } => {
-                let value = self.get_operand_value(operation_value) + self.get_register(dest);
-                self.set_register(dest, value);
-            }
-            Instruction::Mul { dest, value: operation_value } => {
-                let value = self.get_operand_value(operation_value) * self.get_register(dest);
+            Instruction::Add { dest, value: operation_value }
+            | Instruction::Mul { dest, value: operation_value }
+            | Instruction::Mod { dest, value: operation_value } => {
+                let value = get_arithmetic_result(instruction, self.get_register(dest), self.get_operand_value(operation_value))
+                    .ok_or_else(|| get_arithmetic_error(self.instruction_pointer))?;
                 self.set_register(dest, value);
             },
-            Instruction::Mod { dest, value: operation_value } => {
-                let value = self.get_register(dest) % self.get_operand_value(operation_value);
-                self.set_register(dest, value);
-            }
             Instruction::Recover { operand } => {
                 if self.get_operand_value(operand) != 0 {
+                    trace!(Debug, "recovers {:?} at {}", self.last_sound, self.instruction_pointer);
                     self.last_recoverd_sound = self.last_sound;
                 }
             }
             Instruction::Jump {test, offset} => {
                 if self.get_operand_value(test) > 0 {
                     has_jumped = true;
-                    self.instruction_pointer = 
-                        ((self.instruction_pointer as i64) + self.get_operand_value(offset)) as usize;
+                    self.instruction_pointer = get_jump_target(self.instruction_pointer, self.get_operand_value(offset));
                 }
             }
         }
         if !has_jumped {
             self.instruction_pointer += 1;
         }
+        Ok(true)
     }
 
-    fn get_last_recovered_sound(&mut self) -> i64 {
+    pub fn get_last_recovered_sound(&mut self, control: &Control) -> Result<i64, SolveError> {
+        let mut num_executed: u64 = 0;
         while self.last_recoverd_sound.is_none() {
-            self.execute();
+            control.check(num_executed)?;
+            if !self.execute().map_err(SolveError::NoAnswer)? {
+                return Err(SolveError::NoAnswer(String::from("the program ends before recovering a sound")));
+            }
+            num_executed += 1;
         }
-        self.last_recoverd_sound.unwrap_or(0)
+        Ok(self.last_recoverd_sound.unwrap_or(0))
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum ProcessId {
     First,
     Second
 }
 
-impl ProcessId {
-    fn other(&self) -> Self {
-        match &self {
-            ProcessId::First => ProcessId::Second,
-            ProcessId::Second => ProcessId::First
-        }
-    }
-}
-
+/// What the processes tell the main thread; a process reports a value before sending it and
+/// after receiving one, so that the main thread knows of every value still on its way.
 #[derive(Copy, Clone)]
 enum MainMessage {
     Blocked { id: ProcessId },
-    Unblocked { id: ProcessId },
+    Received { id: ProcessId },
     Terminated { id: ProcessId },
-    SentMessage { id: ProcessId }
+    SentMessage { id: ProcessId },
+    Overflowed { instruction_pointer: usize }
 }
 
 #[derive(Copy, Clone)]
@@ -224,22 +205,27 @@ struct ProcessState {
     id: ProcessId,
     is_blocked: bool,
     is_terminated: bool,
-    num_sent_msg: u64
+    num_sent_msg: u64,
+    num_received_msg: u64
 }
 
 impl ProcessState {
     fn new(id: ProcessId) -> Self {
-        ProcessState {id, is_blocked: false, is_terminated: false, num_sent_msg: 0}
+        ProcessState {id, is_blocked: false, is_terminated: false, num_sent_msg: 0, num_received_msg: 0}
     }
 
-    fn is_halted(&self) -> bool {
-        self.is_blocked || self.is_terminated
+    /// A process waiting for a value only halts once it got every value the other one sent.
+    fn is_halted(&self, other: &ProcessState) -> bool {
+        self.is_terminated || (self.is_blocked && self.num_received_msg == other.num_sent_msg)
     }
 
     fn update(&mut self, message: MainMessage) {
         match message {
             MainMessage::Blocked { id } if id == self.id => self.is_blocked = true,
-            MainMessage::Unblocked { id } if id == self.id => self.is_blocked = false,
+            MainMessage::Received { id } if id == self.id => {
+                self.is_blocked = false;
+                self.num_received_msg += 1;
+            },
             MainMessage::Terminated { id } if id == self.id => self.is_terminated = true,
             MainMessage::SentMessage { id } if id == self.id => self.num_sent_msg += 1,
             _ => ()
@@ -254,7 +240,8 @@ struct Process {
     registers: HashMap<u8, i64>,
     main_sender: mpsc::Sender<MainMessage>,
     other_sender: mpsc::Sender<i64>,
-    receiver: mpsc::Receiver<i64>
+    receiver: mpsc::Receiver<i64>,
+    stop: Arc<AtomicBool>
 }
 
 impl Process {
@@ -263,7 +250,8 @@ impl Process {
         instructions_slice: &[Instruction], 
         main_sender: mpsc::Sender<MainMessage>,
         other_sender: mpsc::Sender<i64>,
-        receiver: mpsc::Receiver<i64>
+        receiver: mpsc::Receiver<i64>,
+        stop: Arc<AtomicBool>
     ) -> Self {
         let mut registers: HashMap<u8, i64> = HashMap::new();
         for chr in b'a'..=b'z' {
@@ -273,8 +261,10 @@ impl Process {
             ProcessId::First => 0,
             ProcessId::Second => 1
         };
-        registers.get_mut(&('p' as u8)).map(|mut_ref| *mut_ref = value);
-        let instructions: Vec<Instruction> = instructions_slice.iter().map(|&instr| instr).collect();
+        if let Some(register) = registers.get_mut(&b'p') {
+            *register = value;
+        }
+        let instructions = instructions_slice.to_vec();
         Process {
             id,
             instruction_pointer: 0,
@@ -282,7 +272,8 @@ impl Process {
             registers,
             main_sender,
             other_sender,
-            receiver
+            receiver,
+            stop
         }
     }
 
@@ -303,38 +294,58 @@ impl Process {
         self.registers.get(&dest).map_or(0, |&value| value)
     }
 
-    fn execute(&mut self) {
+    fn terminate(&self) -> Option<()> {
+        trace!(Debug, "{:?} terminates at {}", self.id, self.instruction_pointer);
+        let _ = self.main_sender.send(MainMessage::Terminated { id: self.id });
+        None
+    }
+
+    /// Waits for a value until the main thread says to stop.
+    fn receive(&self) -> Option<i64> {
+        loop {
+            match self.receiver.recv_timeout(RECEIVE_INTERVAL) {
+                Ok(value) => return Some(value),
+                Err(mpsc::RecvTimeoutError::Timeout) if !self.stop.load(Ordering::Relaxed) => {},
+                Err(_) => return None
+            }
+        }
+    }
+
+    /// Returns `None` once the program ended or the process has to stop.
+    fn execute(&mut self) -> Option<()> {
         if self.instruction_pointer >= self.instructions.len() {
-            self.main_sender.send(MainMessage::Terminated { id: self.id }).unwrap();
-            return;
+            return self.terminate();
         }
         let mut has_jumped = false;
-        match self.instructions[self.instruction_pointer] {
+        let instruction = self.instructions[self.instruction_pointer];
+        match instruction {
             Instruction::Sound { operand } => {
-                self.main_sender.send(MainMessage::Unblocked { id: self.id.other() }).unwrap();
-                self.main_sender.send(MainMessage::SentMessage{ id: self.id }).unwrap();
+                self.main_sender.send(MainMessage::SentMessage{ id: self.id }).ok()?;
                 let value = self.get_operand_value(operand);
-                self.other_sender.send(value).unwrap();
+                trace!(Debug, "{:?} sends {} at {}", self.id, value, self.instruction_pointer);
+                self.other_sender.send(value).ok()?;
             },
             Instruction::Set { dest, value: operation_value } => {
                 let value = self.get_operand_value(operation_value);
                 self.set_register(dest, value);
             },
-            Instruction::Add { dest, value: operation_value } => {
-                let value = self.get_operand_value(operation_value) + self.get_register(dest);
-                self.set_register(dest, value);
-            }
-            Instruction::Mul { dest, value: operation_value } => {
-                let value = self.get_operand_value(operation_value) * self.get_register(dest);
-                self.set_register(dest, value);
+            Instruction::Add { dest, value: operation_value }
+            | Instruction::Mul { dest, value: operation_value }
+            | Instruction::Mod { dest, value: operation_value } => {
+                let result = get_arithmetic_result(instruction, self.get_register(dest), self.get_operand_value(operation_value));
+                match result {
+                    Some(value) => self.set_register(dest, value),
+                    None => {
+                        let _ = self.main_sender.send(MainMessage::Overflowed { instruction_pointer: self.instruction_pointer });
+                        return None;
+                    }
+                }
             },
-            Instruction::Mod { dest, value: operation_value } => {
-                let value = self.get_register(dest) % self.get_operand_value(operation_value);
-                self.set_register(dest, value);
-            }
             Instruction::Recover { operand } => {
-                self.main_sender.send(MainMessage::Blocked { id: self.id}).unwrap();
-                let value = self.receiver.recv().unwrap();
+                self.main_sender.send(MainMessage::Blocked { id: self.id}).ok()?;
+                let value = self.receive()?;
+                self.main_sender.send(MainMessage::Received { id: self.id }).ok()?;
+                trace!(Debug, "{:?} receives {} at {}", self.id, value, self.instruction_pointer);
                 if let Operand::Register(register_char) = operand {
                     self.set_register(register_char, value);
                 }
@@ -342,77 +353,268 @@ impl Process {
             Instruction::Jump {test, offset} => {
                 if self.get_operand_value(test) > 0 {
                     has_jumped = true;
-                    let updated_instruction_pointer = (self.instruction_pointer as i64) + self.get_operand_value(offset);
-                    if updated_instruction_pointer < 0 || updated_instruction_pointer >= (self.instructions.len()as i64) {
-                        self.main_sender.send(MainMessage::Terminated { id: self.id }).unwrap();
-                        return;
+                    let updated_instruction_pointer = get_jump_target(self.instruction_pointer, self.get_operand_value(offset));
+                    if updated_instruction_pointer >= self.instructions.len() {
+                        return self.terminate();
                     }
-                    self.instruction_pointer = updated_instruction_pointer
-                     as usize;
+                    self.instruction_pointer = updated_instruction_pointer;
                 }
             }
         }
         if !has_jumped {
             self.instruction_pointer += 1;
         }
+        Some(())
     }
 
     fn execute_loop(&mut self) {
-        loop {
-            self.execute();
-        }
+        while !self.stop.load(Ordering::Relaxed) && self.execute().is_some() {}
     }
 }
 
-fn read_instructions(path: &str) -> Vec<Instruction> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |strings| {
-                strings.iter()
-                    .filter_map(|string| Instruction::get_instruction(string))
-                    .collect()
-            }
-        )
+pub fn parse_instructions(lines: &[String], context: &mut ParseContext) -> Result<Vec<Instruction>, ParseError> {
+    context.parse_lines(
+        lines,
+        "'snd|rcv <operand>' or 'set|add|mul|mod|jgz <register> <operand>'",
+        Instruction::get_instruction
+    )
 }
 
-pub fn solve_part_one() {
-    let instructions = read_instructions("day_eighteen.txt");
-    let mut computer = Computer::new(&instructions);
-    println!("{}", computer.get_last_recovered_sound());
+/// Follows the processes until both ended or wait for values that will never come, and counts the
+/// values the second one, program 1, sent. A thread that is done without its program having ended
+/// panicked, or stopped because the other one did, and the duet has no answer then.
+fn wait_for_halt(
+    main_receiver: &mpsc::Receiver<MainMessage>,
+    handles: &[JoinHandle<()>],
+    control: &Control
+) -> Result<u64, SolveError> {
+    let mut first_process_state = ProcessState::new(ProcessId::First);
+    let mut second_process_state = ProcessState::new(ProcessId::Second);
+    let mut num_messages: u64 = 0;
+    let stopped_error = || SolveError::NoAnswer(String::from("a program stopped before the duet ended"));
+    loop {
+        control.check(num_messages)?;
+        // Looked at before waiting, so that every message of a finished thread is in by the timeout.
+        let is_finished: Vec<bool> = handles.iter().map(|handle| handle.is_finished()).collect();
+        let message = match main_receiver.recv_timeout(Duration::from_millis(1)) {
+            Ok(message) => message,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let states = [&first_process_state, &second_process_state];
+                if is_finished.iter().zip(states.iter()).any(|(&is_finished, state)| is_finished && !state.is_terminated) {
+                    return Err(stopped_error());
+                }
+                continue;
+            },
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Err(stopped_error())
+        };
+        if let MainMessage::Overflowed { instruction_pointer } = message {
+            return Err(SolveError::NoAnswer(get_arithmetic_error(instruction_pointer)));
+        }
+        num_messages += 1;
+        first_process_state.update(message);
+        second_process_state.update(message);
+        if first_process_state.is_halted(&second_process_state) && second_process_state.is_halted(&first_process_state) {
+            break;
+        }
+    }
+    Ok(second_process_state.num_sent_msg)
 }
 
-pub fn solve_part_two() {
-    let instructions = read_instructions("day_eighteen.txt");
+/// Runs each process on its own thread; the threads are stopped and joined before returning, also
+/// when the part is interrupted, and a panic of either thread is raised again on the caller's.
+pub fn get_num_sent_messages(instructions: &[Instruction], control: &Control) -> Result<u64, SolveError> {
+    let stop = Arc::new(AtomicBool::new(false));
     let (first_process_main_sender, main_receiver) = mpsc::channel::<MainMessage>();
     let second_process_main_sender = first_process_main_sender.clone();
     let (first_process_sender, first_process_receiver) = mpsc::channel::<i64>();
     let (second_process_sender, second_process_receiver) = mpsc::channel::<i64>();
     let mut first_process = Process::new(
         ProcessId::First, 
-        &instructions, 
+        instructions, 
         first_process_main_sender, 
         second_process_sender,
-        first_process_receiver
+        first_process_receiver,
+        Arc::clone(&stop)
     );
     let mut second_process = Process::new(
         ProcessId::Second, 
-        &instructions, 
+        instructions, 
         second_process_main_sender, 
         first_process_sender,
-        second_process_receiver
+        second_process_receiver,
+        Arc::clone(&stop)
     );
-    let mut first_process_state = ProcessState::new(ProcessId::First);
-    let mut second_process_state = ProcessState::new(ProcessId::Second);
-    thread::spawn(move || first_process.execute_loop());
-    thread::spawn(move || second_process.execute_loop());
-    loop {
-        let message = main_receiver.recv().unwrap();
-        first_process_state.update(message);
-        second_process_state.update(message);
-        if first_process_state.is_halted() && second_process_state.is_halted() {
-            break;
+    let handles = vec![
+        thread::spawn(move || first_process.execute_loop()),
+        thread::spawn(move || second_process.execute_loop())
+    ];
+    let result = wait_for_halt(&main_receiver, &handles, control);
+    stop.store(true, Ordering::Relaxed);
+    let panics: Vec<_> = handles.into_iter().filter_map(|handle| handle.join().err()).collect();
+    if let Some(payload) = panics.into_iter().next() {
+        panic::resume_unwind(payload);
+    }
+    result
+}
+
+const REGISTERS: [char; 5] = ['a', 'b', 'f', 'i', 'p'];
+const JUMP_OFFSETS: [i64; 8] = [-4, -3, -2, -1, 1, 2, 3, 4];
+
+fn get_random_operand(random: &mut Random) -> String {
+    if random.one_in(2) {
+        random.choose(&REGISTERS).to_string()
+    } else {
+        (random.range(0, 15) as i64 - 5).to_string()
+    }
+}
+
+/// The test of `jgz` is a register or a single digit.
+fn get_random_test(random: &mut Random) -> String {
+    if random.one_in(2) {
+        random.choose(&REGISTERS).to_string()
+    } else {
+        random.range(0, 10).to_string()
+    }
+}
+
+/// Programs that parse, not programs that halt: jumps may leave the program or loop for ever, and
+/// `mul` in a loop overflows sooner or later, which the solver reports as having no answer. The
+/// `mod` instructions only take positive values so that they can't divide by zero.
+pub fn generate_instructions(random: &mut Random) -> Vec<String> {
+    (0..random.range(3, 30))
+        .map(|_| {
+            let register = *random.choose(&REGISTERS);
+            match random.range(0, 7) {
+                0 => format!("snd {}", get_random_operand(random)),
+                1 => format!("set {} {}", register, get_random_operand(random)),
+                2 => format!("add {} {}", register, get_random_operand(random)),
+                3 => format!("mul {} {}", register, get_random_operand(random)),
+                4 => format!("mod {} {}", register, random.range(1, 10)),
+                5 => format!("rcv {}", register),
+                _ => format!("jgz {} {}", get_random_test(random), *random.choose(&JUMP_OFFSETS))
+            }
+        })
+        .collect()
+}
+
+pub struct DaySeventeen;
+
+impl Solution for DaySeventeen {
+    type Input = Vec<Instruction>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_instructions(lines, context)
+    }
+
+    fn part_one(&self, instructions: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        let mut computer = Computer::new(instructions);
+        Ok(Answer::from(computer.get_last_recovered_sound(control)?))
+    }
+
+    fn part_two(&self, instructions: &Self::Input, control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(Some(Answer::from(get_num_sent_messages(instructions, control)?)))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_instructions(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_seventeen", DaySeventeen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::StopReason;
+    use crate::input::InputSource;
+    use crate::runner::{run_part, RunOptions};
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    fn get_instructions(lines: &[&str]) -> Vec<Instruction> {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        parse_instructions(&lines, &mut ParseContext::new(None, true)).unwrap()
+    }
+
+    #[test]
+    fn recovers_the_last_sound_of_the_example() {
+        let example = &[
+            "set a 1",
+            "add a 2",
+            "mul a a",
+            "mod a 5",
+            "snd a",
+            "set a 0",
+            "rcv a",
+            "jgz a -1",
+            "set a 1",
+            "jgz a -2"
+        ];
+        assert_eq!(solve_example(&DaySeventeen, example, Part::One), Some(Answer::Integer(4)));
+    }
+
+    #[test]
+    fn counts_the_values_sent_in_the_example_duet() {
+        let example = &[
+            "snd 1",
+            "snd 2",
+            "snd p",
+            "rcv a",
+            "rcv b",
+            "rcv c",
+            "rcv d"
+        ];
+        assert_eq!(solve_example(&DaySeventeen, example, Part::Two), Some(Answer::Integer(3)));
+    }
+
+    #[test]
+    fn reports_programs_that_jump_out_before_recovering() {
+        let error = DaySeventeen.part_one(&get_instructions(&["snd 1", "jgz 1 -5"]), &Control::new()).unwrap_err();
+        assert_eq!(error, SolveError::NoAnswer(String::from("the program ends before recovering a sound")));
+    }
+
+    #[test]
+    fn fails_the_parts_of_a_program_that_overflows() {
+        let mut registry = Registry::default();
+        register(&mut registry);
+        let input = InputSource::Text(String::from("set a 2\nmul a a\njgz a -1\n"));
+        for &part in &[Part::One, Part::Two] {
+            let run = run_part(&registry.puzzles()[0], part, &input, &RunOptions::default());
+            assert_eq!(run.outcome.to_string(), "error: no answer: instruction 2 overflows or divides by zero");
+        }
+    }
+
+    #[test]
+    fn stops_the_duet_threads_when_interrupted() {
+        let control = Control::new().timeout(Duration::from_secs(0));
+        match DaySeventeen.part_two(&get_instructions(&["jgz 1 0"]), &control) {
+            Err(SolveError::Interrupted(interrupted)) => assert_eq!(interrupted.reason, StopReason::TimedOut),
+            result => panic!("the duet ended with {:?}", result)
+        }
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DaySeventeen);
+    }
+
+    #[test]
+    fn runs_generated_programs_without_panicking() {
+        for seed in 0..150 {
+            let lines = generate_instructions(&mut Random::new(seed));
+            let instructions = parse_instructions(&lines, &mut ParseContext::new(None, true)).unwrap();
+            let control = Control::new().timeout(Duration::from_millis(20));
+            match DaySeventeen.part_one(&instructions, &control) {
+                Ok(_) | Err(SolveError::NoAnswer(_)) | Err(SolveError::Interrupted(_)) => {},
+                Err(error) => panic!("seed {} gives {}", seed, error)
+            }
+            let control = Control::new().timeout(Duration::from_millis(20));
+            match DaySeventeen.part_two(&instructions, &control) {
+                Ok(_) | Err(SolveError::NoAnswer(_)) | Err(SolveError::Interrupted(_)) => {},
+                Err(error) => panic!("seed {} gives {}", seed, error)
+            }
         }
     }
-    println!("{}", first_process_state.num_sent_msg);
-}
\ No newline at end of file
+}
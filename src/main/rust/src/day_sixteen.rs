@@ -1,53 +1,45 @@
-use lazy_static::lazy_static;
-use regex::Regex;
 use std::str;
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::parameters::Parameters;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
-#[derive(Clone, Copy)]
-enum DanceMove {
+#[derive(Clone, Copy, Debug)]
+pub enum DanceMove {
     Spin { length: usize },
     Exchange { first: usize, second: usize },
     Partner { first: char, second: char }
 }
 
 impl DanceMove {
-    fn from_string(string: &str) -> Option<Self> {
-        lazy_static! {
-            static ref SPIN_RE: Regex = Regex::new(r"s(\d+)").unwrap();
-            static ref EXCHANGE_RE: Regex = Regex::new(r"x(\d+)/(\d+)").unwrap();
-            static ref PARTNER_RE: Regex = Regex::new(r"p([a-p])/([a-p])").unwrap();
-        }
-        if let Some(captures) = SPIN_RE.captures(string) {
-            let length = captures.get(1)
-                .and_then(|re_match| {
-                    re_match.as_str().parse::<usize>().ok()
-                })
-                .unwrap_or(0);
-            return Some(DanceMove::Spin { length });
-        }
-        if let Some(captures) = EXCHANGE_RE.captures(string) {
-            let first = captures.get(1)
-                .and_then(|re_match| {
-                    re_match.as_str().parse::<usize>().ok()
-                })
-                .unwrap_or(0);
-            let second = captures.get(2)
-                .and_then(|re_match| {
-                    re_match.as_str().parse::<usize>().ok()
-                })
-                .unwrap_or(0);
-            return Some(DanceMove::Exchange { first, second } );
-        }
-        if let Some(captures) = PARTNER_RE.captures(string) {
-            let first = captures.get(1)
-                .and_then(|re_match| re_match.as_str().chars().next())
-                .unwrap_or(' ');
-            let second = captures.get(2)
-                .and_then(|re_match| re_match.as_str().chars().next())
-                .unwrap_or(' ');
-            return Some(DanceMove::Partner { first, second })
+    fn from_string(string: &str) -> Result<Self, PatternError> {
+        parse_line!(string, {
+            r"s(\d+)" => |length: usize| DanceMove::Spin { length },
+            r"x(\d+)/(\d+)" => |first: usize, second: usize| DanceMove::Exchange { first, second },
+            r"p([a-p])/([a-p])" => |first: char, second: char| DanceMove::Partner { first, second }
+        })
+    }
+
+    /// Moves have to stay within the programs dancing.
+    fn check(&self, programs: &str) -> Result<(), String> {
+        let num_programs = programs.len();
+        match *self {
+            DanceMove::Spin { length } if length > num_programs => {
+                Err(format!("cannot spin {} of {} programs", length, num_programs))
+            },
+            DanceMove::Exchange { first, second } if first.max(second) >= num_programs => {
+                Err(format!("there are only {} programs", num_programs))
+            },
+            DanceMove::Partner { first, second } if !programs.contains(first) || !programs.contains(second) => {
+                Err(format!("only {} are dancing", programs))
+            },
+            _ => Ok(())
         }
-        None
     }
 }
 
@@ -60,64 +52,133 @@ fn get_after_spin(string: &str, length: usize) -> String {
 }
 
 fn get_after_exchange(string: &str, first: usize, second: usize) -> String {
-    let mut bytes: Vec<u8> = string.as_bytes().iter().map(|x| *x).collect();
-    let temp = bytes[first];
-    bytes[first] = bytes[second];
-    bytes[second] = temp;
+    let mut bytes: Vec<u8> = string.as_bytes().to_vec();
+    bytes.swap(first, second);
     let result = str::from_utf8(&bytes).unwrap_or("");
     String::from(result)
 }
 
 fn get_after_partner(string: &str, first: char, second: char) -> String {
-    let mut bytes: Vec<u8> = string.as_bytes().iter().map(|x| *x).collect();
+    let mut bytes: Vec<u8> = string.as_bytes().to_vec();
     let first_code = first as u8;
     let second_code = second as u8;
     let first_index = bytes.iter().position(|&byte| byte == first_code).unwrap_or(0);
     let second_index = bytes.iter().position(|&byte| byte == second_code).unwrap_or(0);
-    let temp = bytes[first_index];
-    bytes[first_index] = bytes[second_index];
-    bytes[second_index] = temp;
+    bytes.swap(first_index, second_index);
     let result = str::from_utf8(&bytes).unwrap_or("");
     String::from(result)
 }
 
 fn get_after_dance_move(string: &str, dance_move: &DanceMove) -> String {
-    match dance_move {
-        &DanceMove::Spin { length } => get_after_spin(string, length),
-        &DanceMove::Exchange { first, second } => get_after_exchange(string, first, second),
-        &DanceMove::Partner { first, second } => get_after_partner(string, first, second)
+    trace!(Trace, "{} before {:?}", string, dance_move);
+    match *dance_move {
+        DanceMove::Spin { length } => get_after_spin(string, length),
+        DanceMove::Exchange { first, second } => get_after_exchange(string, first, second),
+        DanceMove::Partner { first, second } => get_after_partner(string, first, second)
     }
 }
 
-fn get_after_dance_moves(string: &str, dance_moves: &[DanceMove]) -> String {
+pub fn get_after_dance_moves(string: &str, dance_moves: &[DanceMove]) -> String {
     dance_moves.iter()
         .fold(String::from(string), |acc, dance_move| get_after_dance_move(&acc, dance_move))
 }
 
-fn get_dance_moves(string: &str) -> Vec<DanceMove> {
-    string.split(",")
-        .filter_map(|move_str| DanceMove::from_string(move_str))
-        .collect()
+pub fn parse_dance_moves(lines: &[String], programs: &str, context: &mut ParseContext) -> Result<Vec<DanceMove>, ParseError> {
+    let expected = "comma separated moves 's<n>', 'x<n>/<n>' or 'p<a>/<b>'";
+    let (index, line) = lines.iter()
+        .enumerate()
+        .find(|(_, line)| !line.trim().is_empty())
+        .ok_or_else(|| context.error(1, "", expected))?;
+    let mut dance_moves: Vec<DanceMove> = Vec::new();
+    for move_str in line.trim().split(',') {
+        if let Some(dance_move) = context.accept(index + 1, move_str, expected, DanceMove::from_string(move_str))? {
+            let checked = dance_move.check(programs).map(|_| dance_move);
+            if let Some(dance_move) = context.accept(index + 1, move_str, expected, checked)? {
+                dance_moves.push(dance_move);
+            }
+        }
+    }
+    Ok(dance_moves)
 }
 
-fn read_dance_moves(path: &str) -> Vec<DanceMove> {
-    read_lines(path)
-        .map(|strings| {
-            let string = strings[0].clone();
-            get_dance_moves(&string)
+/// Moves for the sixteen programs `a` to `p`.
+pub fn generate_dance_moves(random: &mut Random) -> Vec<String> {
+    let programs: Vec<char> = ('a'..='p').collect();
+    let dance_moves: Vec<String> = (0..random.range(1, 200))
+        .map(|_| match random.range(0, 3) {
+            0 => format!("s{}", random.range(1, 16)),
+            1 => format!("x{}/{}", random.range(0, 16), random.range(0, 16)),
+            _ => format!("p{}/{}", random.choose(&programs), random.choose(&programs))
         })
-        .unwrap_or(vec![])
+        .collect();
+    vec![dance_moves.join(",")]
 }
 
-pub fn test() {
-    let dance_moves_str = "s1,x3/4,pe/b";
-    let string = "abcde";
-    println!("{}", get_after_dance_moves(string, &get_dance_moves(dance_moves_str)));
+pub struct DaySixteen {
+    pub programs: String
+}
+
+impl Default for DaySixteen {
+    fn default() -> Self {
+        DaySixteen { programs: String::from("abcdefghijklmnop") }
+    }
+}
+
+impl Solution for DaySixteen {
+    type Input = Vec<DanceMove>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_dance_moves(lines, &self.programs, context)
+    }
+
+    fn part_one(&self, dance_moves: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_after_dance_moves(&self.programs, dance_moves)))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_dance_moves(random))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        parameters.update("programs", &mut self.programs)?;
+        let programs: Vec<char> = self.programs.chars().collect();
+        let is_valid = programs.iter()
+            .enumerate()
+            .all(|(index, program)| program.is_ascii_lowercase() && !programs[..index].contains(program));
+        if programs.is_empty() || !is_valid {
+            return Err(format!("programs must be distinct lowercase letters, not '{}'", self.programs));
+        }
+        Ok(())
+    }
+}
 
+pub fn register(registry: &mut Registry) {
+    registry.register("day_sixteen", DaySixteen::default());
 }
 
-pub fn solve_part_one() {
-    let dance_moves = read_dance_moves("day_sixteen.txt");
-    let string = "abcdefghijklmnop";
-    println!("{}", get_after_dance_moves(string, &dance_moves));
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, get_example_parse_error, solve_example, Part};
+
+    #[test]
+    fn dances_the_example_moves() {
+        let solution = DaySixteen { programs: String::from("abcde") };
+        let answer = solve_example(&solution, &["s1,x3/4,pe/b"], Part::One);
+        assert_eq!(answer, Some(Answer::Text(String::from("baedc"))));
+    }
+
+    #[test]
+    fn rejects_moves_outside_the_programs() {
+        let solution = DaySixteen { programs: String::from("abcde") };
+        let error = get_example_parse_error(&solution, &["s1,x3/99"]);
+        assert_eq!(error.to_string(), "<input>:1: expected comma separated moves 's<n>', 'x<n>/<n>' or 'p<a>/<b>', found 'x3/99' (there are only 5 programs)");
+        assert_eq!(get_example_parse_error(&solution, &["s99"]).text, "s99");
+        assert_eq!(get_example_parse_error(&solution, &["pa/p"]).text, "pa/p");
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DaySixteen::default());
+    }
+}
@@ -1,4 +1,12 @@
 use std::str;
+use crate::registry::Registry;
+use crate::parameters::Parameters;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
 
 fn get_dragon_curve_one_step(data: &[u8]) -> Vec<u8> {
     let mut updated_data = vec![0u8; 2 * data.len() + 1];
@@ -19,7 +27,7 @@ fn get_dragon_curve(data: &[u8], size: usize) ->  Vec<u8> {
     while current_data.len() < size {
         current_data = get_dragon_curve_one_step(&current_data);
     }
-    (&current_data.as_slice()[0..size]).to_vec()
+    current_data[0..size].to_vec()
 }
 
 fn get_checksum_step(data: &[u8]) -> Vec<u8> {
@@ -36,24 +44,25 @@ fn get_checksum_step(data: &[u8]) -> Vec<u8> {
 
 fn get_checksum(data: &[u8]) -> Vec<u8> {
     let mut current_data = data.to_vec();
-    while current_data.len() % 2 == 0 {
+    while current_data.len().is_multiple_of(2) {
+        trace!(Debug, "checksum of {} bits", current_data.len());
         current_data = get_checksum_step(&current_data);
     }
     current_data
 }
 
-fn get_final_data(data: &[u8], size: usize) -> Vec<u8> {
+pub fn get_final_data(data: &[u8], size: usize) -> Vec<u8> {
     let dragon_curve = get_dragon_curve(data, size);
     get_checksum(&dragon_curve)
 }
 
-fn get_data_from_string(string: &str) -> Vec<u8> {
+pub fn get_data_from_string(string: &str) -> Vec<u8> {
     string.as_bytes().iter()
         .map(|&byte| if byte == b'1' { 1u8 } else { 0u8 })
         .collect()
 }
 
-fn get_string_from_data(data: &[u8]) -> String {
+pub fn get_string_from_data(data: &[u8]) -> String {
     let bytes: Vec<_> = data.iter()
         .map(|&byte| if byte == 1 { b'1' } else { b'0' })
         .collect();
@@ -61,7 +70,81 @@ fn get_string_from_data(data: &[u8]) -> String {
     String::from(string)
 }
 
-pub fn solve_part_one(data: &str, size: usize) -> String {
-    let final_data = get_final_data(&get_data_from_string(data), size);
-    get_string_from_data(&final_data)
+pub fn generate_data(random: &mut Random) -> Vec<String> {
+    let data: String = (0..random.range(1, 20))
+        .map(|_| if random.one_in(2) { '1' } else { '0' })
+        .collect();
+    vec![data]
+}
+
+pub struct DaySixteen2016 {
+    pub part_one_size: usize,
+    pub part_two_size: usize
+}
+
+impl Default for DaySixteen2016 {
+    fn default() -> Self {
+        DaySixteen2016 { part_one_size: 272, part_two_size: 35651584 }
+    }
+}
+
+impl Solution for DaySixteen2016 {
+    type Input = Vec<u8>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        context.parse_first(lines, "a string of '0' and '1'", |line| {
+            if line.chars().all(|chr| chr == '0' || chr == '1') {
+                Some(get_data_from_string(line))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn part_one(&self, data: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_string_from_data(&get_final_data(data, self.part_one_size))))
+    }
+
+    fn part_two(&self, data: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(Some(Answer::from(get_string_from_data(&get_final_data(data, self.part_two_size)))))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_data(random))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        parameters.update("part_one_size", &mut self.part_one_size)?;
+        parameters.update("part_two_size", &mut self.part_two_size)?;
+        if self.part_one_size == 0 || self.part_two_size == 0 {
+            return Err(String::from("the disk sizes must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_sixteen_2016", DaySixteen2016::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    #[test]
+    fn fills_the_example_disk() {
+        let solution = DaySixteen2016 { part_one_size: 20, part_two_size: 20 };
+        assert_eq!(solve_example(&solution, &["10000"], Part::One), Some(Answer::Text(String::from("01100"))));
+    }
+
+    #[test]
+    fn computes_the_example_checksum() {
+        assert_eq!(get_string_from_data(&get_checksum(&get_data_from_string("110010110100"))), "100");
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DaySixteen2016::default());
+    }
 }
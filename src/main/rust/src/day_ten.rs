@@ -1,19 +1,26 @@
-use regex::Regex;
-use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::cmp::min;
 use std::cmp::max;
-use crate::utils::read_lines;
+use std::str::FromStr;
+use crate::registry::Registry;
+use crate::parameters::Parameters;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum Output {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Output {
     OutputBin(usize),
     Bot(usize)
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
-enum Instruction {
+pub enum Instruction {
     Value {
         value: usize,
         bot: usize
@@ -25,55 +32,38 @@ enum Instruction {
     }
 }
 
-impl Instruction {
-    fn get_value_instr(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"value (\d+) goes to bot (\d+)").unwrap();
+/// Reads `bot <n>` or `output <n>`.
+impl FromStr for Output {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut split = string.split(' ');
+        let kind = split.next().ok_or(())?;
+        let id = split.next().and_then(|id| id.parse::<usize>().ok()).ok_or(())?;
+        match kind {
+            "output" => Ok(Output::OutputBin(id)),
+            "bot" => Ok(Output::Bot(id)),
+            _ => Err(())
         }
-        let captures = RE.captures(string)?;
-        let value: usize = captures.get(1)?.as_str().parse().ok()?;
-        let bot: usize = captures.get(2)?.as_str().parse().ok()?;
-        Some(Instruction::Value { value, bot })
     }
+}
 
-    fn get_low_high_instr(string: &str) -> Option<Instruction> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"bot (\d+) gives low to (output|bot) (\d+) and high to (output|bot) (\d+)").unwrap();
-        }
-        let captures = RE.captures(string)?;
-        let bot: usize = captures.get(1)?.as_str().parse().ok()?;
-        let low_output_id: usize = captures.get(3)?.as_str().parse().ok()?;
-        let low_output_str_type = captures.get(2)?.as_str();
-        let low_output = match low_output_str_type {
-            "output" => Some(Output::OutputBin(low_output_id)),
-            "bot" => Some(Output::Bot(low_output_id)),
-            _ => None
-        }?;
-        let high_output_id: usize = captures.get(5)?.as_str().parse().ok()?;
-        let high_output_str_type = captures.get(4)?.as_str();
-        let high_output = match high_output_str_type {
-            "output" => Some(Output::OutputBin(high_output_id)),
-            "bot" => Some(Output::Bot(high_output_id)),
-            _ => None
-        }?;
-        Some(Instruction::LowHigh { bot, low_output, high_output })
-    }
-
-    fn get_instruction(string: &str) -> Option<Instruction> {
-        Self::get_value_instr(string).or(Self::get_low_high_instr(string))
+impl Instruction {
+    pub fn get_instruction(string: &str) -> Result<Instruction, PatternError> {
+        parse_line!(string, {
+            r"value (\d+) goes to bot (\d+)" => |value: usize, bot: usize| Instruction::Value { value, bot },
+            r"bot (\d+) gives low to ((?:output|bot) \d+) and high to ((?:output|bot) \d+)" =>
+                |bot: usize, low_output: Output, high_output: Output| Instruction::LowHigh { bot, low_output, high_output }
+        })
     }
 }
 
-fn read_instructions(path: &str) -> Vec<Instruction> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |strings| {
-                strings.iter()
-                    .filter_map(|string| Instruction::get_instruction(string))
-                    .collect()
-            }
-        )
+pub fn parse_instructions(lines: &[String], context: &mut ParseContext) -> Result<Vec<Instruction>, ParseError> {
+    context.parse_lines(
+        lines,
+        "'value <n> goes to bot <n>' or 'bot <n> gives low to <bot|output> <n> and high to <bot|output> <n>'",
+        Instruction::get_instruction
+    )
 }
 
 struct State {
@@ -144,37 +134,30 @@ impl State {
         }
     }
 
-    fn update(&mut self, instruction: &[Instruction]) -> Option<usize> {
-        let (bot, low_output, high_output) = self.bots.iter()
-            .find_map(|(&bot_id, values)| {
-                if values.len() >= 2 {
-                    let instr_opt = instruction.iter()
-                        .find_map(|instr| {
-                            if let &Instruction::LowHigh { bot, low_output, high_output } = instr {
-                                if bot == bot_id {
-                                    Some((low_output, high_output))
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        });
-                    if let Some((low_output, high_output)) = instr_opt {
+    /// A bot holding two chips along with where it hands them, or `None` once every chip has come
+    /// to rest.
+    fn get_ready_bot(&self, instructions: &[Instruction]) -> Option<(usize, Output, Output)> {
+        self.bots.iter()
+            .filter(|(_, values)| values.len() >= 2)
+            .find_map(|(&bot_id, _)| {
+                instructions.iter().find_map(|instruction| match *instruction {
+                    Instruction::LowHigh { bot, low_output, high_output } if bot == bot_id => {
                         Some((bot_id, low_output, high_output))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }).unwrap();
-        let max_value = self.bots[&bot].iter().max().map(|&val| val).unwrap_or(0);
-        let min_value = self.bots[&bot].iter().min().map(|&val| val).unwrap_or(0);
+                    },
+                    _ => None
+                })
+            })
+    }
+
+    /// Has the bot hand on its chips, telling whether they were the two values looked for.
+    fn update(&mut self, bot: usize, low_output: Output, high_output: Output) -> bool {
+        let max_value = self.bots[&bot].iter().max().copied().unwrap_or(0);
+        let min_value = self.bots[&bot].iter().min().copied().unwrap_or(0);
         if let Some(values) = self.bots.get_mut(&bot) {
             remove(values, max_value);
             remove(values, min_value);
         }
+        trace!(Debug, "bot {} gives low {} to {:?} and high {} to {:?}", bot, min_value, low_output, max_value, high_output);
         match low_output {
             Output::Bot(output_bot) => {
                 if let Some(values) = self.bots.get_mut(&output_bot) {
@@ -199,22 +182,132 @@ impl State {
                 }
             }
         };
-        if max_value == max(self.first_value, self.second_value) && min_value == min(self.first_value, self.second_value) {
-            Some(bot)
-        } else {
-            None
+        max_value == max(self.first_value, self.second_value) && min_value == min(self.first_value, self.second_value)
+    }
+}
+
+fn get_random_output(random: &mut Random, bot: u64, num_bots: u64) -> String {
+    if bot + 1 < num_bots && !random.one_in(3) {
+        format!("bot {}", random.range(bot + 1, num_bots))
+    } else {
+        format!("output {}", random.range(0, num_bots))
+    }
+}
+
+/// Bots only hand chips to bots with higher numbers, so chips can't go around in circles, but some
+/// bots may never get two chips. The two chips looked for are always among the values, though no
+/// bot may end up comparing them.
+pub fn generate_instructions(random: &mut Random, first_value: usize, second_value: usize) -> Vec<String> {
+    let num_bots = random.range(2, 12);
+    let chips = [first_value as u64, second_value as u64];
+    let mut values: Vec<u64> = (1..=2 * num_bots + 10).filter(|value| !chips.contains(value)).collect();
+    random.shuffle(&mut values);
+    values.splice(0..0, chips.iter().copied());
+    let mut lines: Vec<String> = Vec::new();
+    for bot in 0..num_bots {
+        let low_output = get_random_output(random, bot, num_bots);
+        let high_output = get_random_output(random, bot, num_bots);
+        lines.push(format!("bot {} gives low to {} and high to {}", bot, low_output, high_output));
+    }
+    let num_values = random.range(2, 2 * num_bots + 1) as usize;
+    for &value in values.iter().take(num_values) {
+        lines.push(format!("value {} goes to bot {}", value, random.range(0, num_bots)));
+    }
+    random.shuffle(&mut lines);
+    lines
+}
+
+pub struct DayTen {
+    pub first_value: usize,
+    pub second_value: usize
+}
+
+impl Default for DayTen {
+    fn default() -> Self {
+        DayTen { first_value: 17, second_value: 61 }
+    }
+}
+
+impl Solution for DayTen {
+    type Input = Vec<Instruction>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_instructions(lines, context)
+    }
+
+    fn part_one(&self, instructions: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        let mut state = State::new(self.first_value, self.second_value);
+        state.initialize(instructions);
+        let mut num_steps: u64 = 0;
+        loop {
+            control.check(num_steps)?;
+            let (bot, low_output, high_output) = state.get_ready_bot(instructions).ok_or_else(|| {
+                SolveError::NoAnswer(format!("no bot compares chips {} and {}", self.first_value, self.second_value))
+            })?;
+            if state.update(bot, low_output, high_output) {
+                return Ok(Answer::from(bot));
+            }
+            num_steps += 1;
+        }
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_instructions(random, self.first_value, self.second_value))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        parameters.update("first_value", &mut self.first_value)?;
+        parameters.update("second_value", &mut self.second_value)?;
+        if self.first_value == self.second_value {
+            return Err(String::from("first_value and second_value must be different chips"));
         }
+        Ok(())
     }
 }
 
-pub fn solve_part_one() {
-    let instructions = read_instructions("day_ten.txt");
-    let mut state = State::new(17, 61);
-    state.initialize(&instructions);
-    loop {
-        if let Some(bot) = state.update(&instructions) {
-            println!("{}", bot);
-            break;
+pub fn register(registry: &mut Registry) {
+    registry.register("day_ten", DayTen::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "value 5 goes to bot 2",
+        "bot 2 gives low to bot 1 and high to bot 0",
+        "value 3 goes to bot 1",
+        "bot 1 gives low to output 1 and high to bot 0",
+        "bot 0 gives low to output 2 and high to output 0",
+        "value 2 goes to bot 2"
+    ];
+
+    #[test]
+    fn finds_the_bot_comparing_the_example_chips() {
+        let solution = DayTen { first_value: 2, second_value: 5 };
+        assert_eq!(solve_example(&solution, EXAMPLE, Part::One), Some(Answer::Integer(2)));
+    }
+
+    #[test]
+    fn reports_chips_no_bot_compares() {
+        let solution = DayTen { first_value: 2, second_value: 4 };
+        let lines: Vec<String> = EXAMPLE.iter().map(|line| line.to_string()).collect();
+        let instructions = solution.parse(&lines, &mut ParseContext::new(None, true)).unwrap();
+        let error = solution.part_one(&instructions, &Control::new()).unwrap_err();
+        assert_eq!(error.to_string(), "no answer: no bot compares chips 2 and 4");
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTen::default());
+    }
+
+    #[test]
+    fn generates_the_chips_looked_for() {
+        let lines = generate_instructions(&mut Random::new(0), 17, 61);
+        for value in &[17, 61] {
+            assert!(lines.iter().any(|line| line.starts_with(&format!("value {} ", value))), "{:?}", lines);
         }
     }
-}
\ No newline at end of file
+}
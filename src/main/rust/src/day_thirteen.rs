@@ -1,7 +1,14 @@
-use lazy_static::lazy_static;
-use regex::Regex;
 use std::collections::HashMap;
-use crate::utils::read_lines;
+use std::time::Duration;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::crosscheck::CrossCheck;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
 #[derive(Clone)]
 enum Direction {
@@ -10,54 +17,40 @@ enum Direction {
 }
 
 #[derive(Clone)]
-struct ScannerState {
+pub struct ScannerState {
     position: u64,
     depth: u64,
     direction: Direction,
     layer: u64
 }
 
-struct Scanner {
+pub struct Scanner {
     layer: u64,
     depth: u64
 }
 
 impl Scanner {
-    fn from_string(string: &str) -> Option<Self> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"(\d+): (\d+)").unwrap();
-        }
-        let captures = RE.captures(string)?;
-        let layer = captures.get(1)
-            .and_then(|re_match| re_match.as_str().parse::<u64>().ok())?;
-        let depth = captures.get(2)
-            .and_then(|re_match| re_match.as_str().parse::<u64>().ok())?;
-        Some(Scanner{ layer, depth })
+    fn from_string(string: &str) -> Result<Self, PatternError> {
+        parse_line!(string, r"(\d+): (\d+)" => Scanner { layer: u64, depth: u64 })
     }
 }
 
 impl ScannerState {
 
-    fn from_string(string: &str) -> Option<Self> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"(\d+): (\d+)").unwrap();
+    fn from_scanner(scanner: &Scanner) -> Self {
+        ScannerState {
+            position: 0,
+            depth: scanner.depth,
+            layer: scanner.layer,
+            direction: Direction::Down
         }
-        let captures = RE.captures(string)?;
-        let layer = captures.get(1)
-            .and_then(|re_match| re_match.as_str().parse::<u64>().ok())?;
-        let depth = captures.get(2)
-            .and_then(|re_match| re_match.as_str().parse::<u64>().ok())?;
-        Some(
-            ScannerState {
-                position: 0,
-                depth,
-                layer,
-                direction: Direction::Down
-            }
-        )
     }
 
+    /// A scanner with a range of one has nowhere to go and stays at the top.
     fn update(&mut self) {
+        if self.depth < 2 {
+            return;
+        }
         match self.direction {
             Direction::Down => {
                 if self.position == self.depth - 1 {
@@ -87,10 +80,11 @@ impl ScannerState {
     }
 } 
 
-fn get_severity_level(scanners: &mut HashMap<u64, ScannerState>) -> u64 {
+fn get_severity_level(scanners: &mut HashMap<u64, ScannerState>, control: &Control) -> Result<u64, Interrupted> {
     let mut severity_level: u64 = 0;
-    let num_layers: u64 = scanners.keys().max().map(|u_ref| *u_ref).unwrap_or(0);
+    let num_layers: u64 = scanners.keys().max().copied().unwrap_or(0);
     for packet_layer in 0..=num_layers {
+        control.check(packet_layer)?;
         if let Some(scanner) = scanners.get(&packet_layer) {
             if scanner.is_at_top() {
                 severity_level += scanner.get_severity();
@@ -100,31 +94,20 @@ fn get_severity_level(scanners: &mut HashMap<u64, ScannerState>) -> u64 {
             scanner.update();
         }
     }
-    severity_level
-}
-
-fn read_scanners_state(path: &str) -> Vec<ScannerState> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |strings| {
-                strings.into_iter()
-                    .filter_map(|string| ScannerState::from_string(&string))
-                    .collect()
-            }
-        )
+    Ok(severity_level)
 }
 
-fn read_scanners(path: &str) -> Vec<Scanner> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |strings| {
-                strings.into_iter()
-                    .filter_map(|string| Scanner::from_string(&string))
-                    .collect()
-            }
-        )
+pub fn parse_scanners(lines: &[String], context: &mut ParseContext) -> Result<Vec<Scanner>, ParseError> {
+    let mut scanners: Vec<Scanner> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(scanner) = context.accept(index + 1, line, "'<layer>: <depth>'", Scanner::from_string(line))? {
+            scanners.push(scanner);
+        }
+    }
+    Ok(scanners)
 }
 
 fn to_scanners_map(vector: Vec<ScannerState>) -> HashMap<u64, ScannerState> {
@@ -144,7 +127,7 @@ fn update_scanners(scanners: &mut HashMap<u64, ScannerState>) {
 fn is_probe_caught(scanners: &HashMap<u64, ScannerState>) -> bool {
     let num_level = scanners
         .keys()
-        .map(|level_ref| *level_ref)
+        .copied()
         .max()
         .unwrap_or(0);
     let mut current_scanners = scanners.clone();
@@ -159,14 +142,20 @@ fn is_probe_caught(scanners: &HashMap<u64, ScannerState>) -> bool {
     false
 }
 
-fn get_delay(scanners: &[Scanner]) -> u64 {
+/// There is no safe delay when a scanner never leaves the top.
+pub fn get_delay(scanners: &[Scanner], control: &Control) -> Result<Option<u64>, Interrupted> {
+    if scanners.iter().any(|scanner| scanner.depth < 2) {
+        return Ok(None);
+    }
     let mut delay: u64 = 0;
     loop {
+        control.check(delay)?;
         let mut is_caught = false;
         for scanner in scanners {
             let current_time = delay + scanner.layer;
             let period = 2 * (scanner.depth - 1);
-            if current_time % period == 0 {
+            if current_time.is_multiple_of(period) {
+                trace!(Trace, "delay {} is caught at layer {}", delay, scanner.layer);
                 is_caught = true;
             }
         }
@@ -176,18 +165,123 @@ fn get_delay(scanners: &[Scanner]) -> u64 {
             break;
         }
     }
-    delay
+    Ok(Some(delay))
+}
+
+/// Finds the delay by stepping every scanner one picosecond at a time, the way the puzzle tells
+/// it; slow, but a reference for `get_delay`. Gives up after `max_delay`.
+pub fn get_delay_by_simulation(scanners: &[Scanner], max_delay: u64) -> Option<u64> {
+    let scanners_state = scanners.iter().map(ScannerState::from_scanner).collect();
+    let mut scanners_map = to_scanners_map(scanners_state);
+    for delay in 0..=max_delay {
+        if !is_probe_caught(&scanners_map) {
+            return Some(delay);
+        }
+        update_scanners(&mut scanners_map);
+    }
+    None
+}
+
+fn cross_check_delay(lines: &[String]) -> Option<(String, String)> {
+    let scanners = parse_scanners(lines, &mut ParseContext::new(None, true)).ok()?;
+    let fast = get_delay(&scanners, &Control::new().timeout(Duration::from_secs(1))).ok()?;
+    let reference = get_delay_by_simulation(&scanners, 10_000);
+    if reference.is_none() && fast.is_some() {
+        return None;
+    }
+    let describe = |delay: Option<u64>| delay.map_or(String::from("none"), |delay| delay.to_string());
+    Some((describe(reference), describe(fast)))
+}
+
+
+pub fn generate_scanners(random: &mut Random) -> Vec<String> {
+    let mut layer = random.range(0, 3);
+    let mut lines: Vec<String> = Vec::new();
+    for _ in 0..random.range(1, 20) {
+        let depth = if random.one_in(8) { 1 } else { random.range(2, 12) };
+        lines.push(format!("{}: {}", layer, depth));
+        layer += random.range(1, 4);
+    }
+    lines
 }
 
+pub struct DayThirteen;
 
+impl Solution for DayThirteen {
+    type Input = Vec<Scanner>;
 
-pub fn solve_part_one() {
-    let scanners_vec = read_scanners_state("day_thirteen.txt");
-    let mut scanners_map = to_scanners_map(scanners_vec);
-    println!("{}", get_severity_level(&mut scanners_map));
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_scanners(lines, context)
+    }
+
+    fn part_one(&self, scanners: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        let scanners_state = scanners.iter().map(ScannerState::from_scanner).collect();
+        let mut scanners_map = to_scanners_map(scanners_state);
+        Ok(Answer::from(get_severity_level(&mut scanners_map, control)?))
+    }
+
+    fn part_two(&self, scanners: &Self::Input, control: &Control) -> Result<Option<Answer>, SolveError> {
+        let delay = get_delay(scanners, control)?
+            .ok_or_else(|| SolveError::NoAnswer(String::from("a scanner with a range of 1 never leaves the top")))?;
+        Ok(Some(Answer::from(delay)))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_scanners(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_thirteen", DayThirteen);
+}
+
+pub fn register_cross_checks(checks: &mut Vec<CrossCheck>) {
+    checks.push(CrossCheck::new("2017 13 delay", 2017, 13, cross_check_delay));
 }
 
-pub fn solve_part_two() {
-    let scanners = read_scanners("day_thirteen.txt");
-    println!("{}", get_delay(&scanners));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "0: 3",
+        "1: 2",
+        "4: 4",
+        "6: 4"
+    ];
+
+    #[test]
+    fn sums_the_severity_of_the_example_trip() {
+        assert_eq!(solve_example(&DayThirteen, EXAMPLE, Part::One), Some(Answer::Integer(24)));
+    }
+
+    #[test]
+    fn finds_the_example_delay() {
+        assert_eq!(solve_example(&DayThirteen, EXAMPLE, Part::Two), Some(Answer::Integer(10)));
+    }
+
+    #[test]
+    fn simulates_the_example_delay() {
+        let lines: Vec<String> = EXAMPLE.iter().map(|line| line.to_string()).collect();
+        let scanners = parse_scanners(&lines, &mut ParseContext::new(None, true)).unwrap();
+        assert_eq!(get_delay_by_simulation(&scanners, 100), Some(10));
+        assert_eq!(get_delay_by_simulation(&scanners, 5), None);
+    }
+
+    #[test]
+    fn counts_scanners_that_cannot_move() {
+        let example = &["0: 3", "1: 1", "2: 2"];
+        assert_eq!(solve_example(&DayThirteen, example, Part::One), Some(Answer::Integer(5)));
+        let lines: Vec<String> = example.iter().map(|line| line.to_string()).collect();
+        let scanners = parse_scanners(&lines, &mut ParseContext::new(None, true)).unwrap();
+        let error = DayThirteen.part_two(&scanners, &Control::new()).unwrap_err();
+        assert_eq!(error.to_string(), "no answer: a scanner with a range of 1 never leaves the top");
+        assert_eq!(get_delay_by_simulation(&scanners, 100), None);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayThirteen);
+    }
 }
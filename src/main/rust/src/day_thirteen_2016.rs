@@ -1,5 +1,14 @@
 use std::collections::HashMap;
 use md5;
+use crate::checkpoint::Checkpoint;
+use crate::registry::Registry;
+use crate::parameters::Parameters;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
 
 fn get_triplet(bytes: &[u8]) -> Option<u8> {
     for index in 0..(bytes.len() - 2) {
@@ -55,15 +64,14 @@ impl KeyFinder {
             *value -= 1;
         }
         let mut sorted_keys: Vec<_> = self.possible_keys.keys()
-            .map(|&key| key)
+            .copied()
             .collect();
         sorted_keys.sort();
         for &(key_index, key_byte) in &sorted_keys {
-            if has_quintuplet(bytes, key_byte) {
-                if self.num_keys != self.goal_num_keys {
-                    self.num_keys += 1;
-                    self.last_key_index = Some(key_index);
-                }
+            if has_quintuplet(bytes, key_byte) && self.num_keys != self.goal_num_keys {
+                trace!(Debug, "key {} at index {} confirmed at index {}", self.num_keys + 1, key_index, index);
+                self.num_keys += 1;
+                self.last_key_index = Some(key_index);
             }
         }
         if let Some(new_key_byte) = get_triplet(bytes) {
@@ -75,8 +83,9 @@ impl KeyFinder {
         self.num_keys == self.goal_num_keys
     }
 
-    fn get_key_index(&self) -> u64 {
-        self.last_key_index.unwrap()
+    /// Only `None` when no key was asked for.
+    fn get_key_index(&self) -> Option<u64> {
+        self.last_key_index
     }
 }
 
@@ -86,53 +95,194 @@ fn get_hash_bytes(string: &str, index: u64) -> Vec<u8> {
     let hash_string = owned_string + &index_string;
     let digest = md5::compute(hash_string.as_bytes());
     let digest_string = format!("{:x}", digest);
-    digest_string.as_bytes().iter().map(|&byte| byte).collect()
+    digest_string.into_bytes()
 }
 
 fn get_stretched_hash_bytes(string: &str, index: u64, output: &mut [u8]) {
-    let owned_string = string.to_string();
-    let index_string = index.to_string();
-    let hash_string = owned_string + &index_string;
-    let mut hash_bytes: [u8; 32] = [0; 32];
-    &hash_bytes[0..hash_string.len()].copy_from_slice(hash_string.as_bytes());
-    let mut length: usize = hash_string.as_bytes().len();
+    let mut hash_bytes: Vec<u8> = format!("{}{}", string, index).into_bytes();
     for _ in 0..2017 {
-        let digest = md5::compute(&hash_bytes[0..length]);
-        let digest_string = format!("{:x}", digest);
-        let digest_bytes = digest_string.as_bytes();
-        &hash_bytes.copy_from_slice(digest_bytes);
-        length = 32;
+        hash_bytes = format!("{:x}", md5::compute(&hash_bytes)).into_bytes();
     }
     output.copy_from_slice(&hash_bytes);
 }
 
-fn get_index_key(string: &str, goal: u64) -> u64 {
+pub fn get_index_key(string: &str, goal: u64, control: &Control) -> Result<Option<u64>, Interrupted> {
     let mut key_finder: KeyFinder = KeyFinder::new(goal);
     let mut index: u64 = 0;
     while !key_finder.is_goal() {
+        control.check(index)?;
         let bytes = get_hash_bytes(string, index);
         key_finder.update(index, &bytes);
         index += 1;
     }
-    key_finder.get_key_index()
+    Ok(key_finder.get_key_index())
 }
 
-fn get_stretched_index_key(string: &str, goal: u64) -> u64 {
-    let mut key_finder: KeyFinder = KeyFinder::new(goal);
-    let mut index: u64 = 0;
-    while !key_finder.is_goal() {
+/// The next index to hash and the keys found before it.
+struct Search {
+    index: u64,
+    key_finder: KeyFinder
+}
+
+/// Written as `<index> <keys found> <last key index or ->`, then `<index> <byte> <lifetime>` for
+/// every possible key still waiting for its quintuplet.
+impl Checkpoint for Search {
+    fn to_lines(&self) -> Vec<String> {
+        let finder = &self.key_finder;
+        let last_key_index = finder.last_key_index.map_or(String::from("-"), |index| index.to_string());
+        let mut lines = vec![format!("{} {} {}", self.index, finder.num_keys, last_key_index)];
+        let mut possible_keys: Vec<(&(u64, u8), &u64)> = finder.possible_keys.iter().collect();
+        possible_keys.sort();
+        lines.extend(possible_keys.iter().map(|&(&(index, byte), lifetime)| format!("{} {} {}", index, byte, lifetime)));
+        lines
+    }
+
+    fn from_lines(lines: &[String]) -> Option<Self> {
+        let mut header = lines.first()?.split(' ');
+        let index = header.next()?.parse::<u64>().ok()?;
+        let num_keys = header.next()?.parse::<u64>().ok()?;
+        let last_key_index = match header.next()? {
+            "-" => None,
+            value => Some(value.parse::<u64>().ok()?)
+        };
+        let mut possible_keys: HashMap<(u64, u8), u64> = HashMap::new();
+        for line in &lines[1..] {
+            let mut fields = line.split(' ');
+            let key = (fields.next()?.parse::<u64>().ok()?, fields.next()?.parse::<u8>().ok()?);
+            possible_keys.insert(key, fields.next()?.parse::<u64>().ok()?);
+        }
+        let key_finder = KeyFinder { possible_keys, num_keys, last_key_index, goal_num_keys: 0 };
+        Some(Search { index, key_finder })
+    }
+}
+
+/// Goes on from a saved search unless it already found more keys than asked for.
+pub fn get_stretched_index_key(string: &str, goal: u64, control: &Control) -> Result<Option<u64>, Interrupted> {
+    let mut search = control.restore::<Search>()
+        .filter(|search| search.key_finder.num_keys <= goal)
+        .unwrap_or_else(|| Search { index: 0, key_finder: KeyFinder::new(goal) });
+    search.key_finder.goal_num_keys = goal;
+    while !search.key_finder.is_goal() {
+        control.check_saving(search.index, &search)?;
         let mut bytes: [u8; 32] = [0; 32];
-        get_stretched_hash_bytes(string, index, &mut bytes);
-        key_finder.update(index, &bytes);
-        index += 1;
+        get_stretched_hash_bytes(string, search.index, &mut bytes);
+        search.key_finder.update(search.index, &bytes);
+        search.index += 1;
+    }
+    Ok(search.key_finder.get_key_index())
+}
+
+pub fn generate_salt(random: &mut Random) -> Vec<String> {
+    vec![random.word(8)]
+}
+
+pub struct DayThirteen2016 {
+    pub goal_num_keys: u64
+}
+
+impl Default for DayThirteen2016 {
+    fn default() -> Self {
+        DayThirteen2016 { goal_num_keys: 64 }
+    }
+}
+
+impl Solution for DayThirteen2016 {
+    type Input = String;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        context.parse_first(lines, "a salt string", |line| Some(line.to_string()))
+    }
+
+    fn part_one(&self, salt: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        get_index_key(salt, self.goal_num_keys, control)?
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::NoAnswer(String::from("no key was asked for")))
+    }
+
+    fn part_two(&self, salt: &Self::Input, control: &Control) -> Result<Option<Answer>, SolveError> {
+        get_stretched_index_key(salt, self.goal_num_keys, control)?
+            .map(|index| Some(Answer::from(index)))
+            .ok_or_else(|| SolveError::NoAnswer(String::from("no key was asked for")))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_salt(random))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        parameters.update("goal_num_keys", &mut self.goal_num_keys)?;
+        if self.goal_num_keys == 0 {
+            return Err(String::from("goal_num_keys must be at least 1"));
+        }
+        Ok(())
     }
-    key_finder.get_key_index()
 }
 
-pub fn solve_part_one() {
-    println!("{}", get_index_key("cuanljph", 64));
+pub fn register(registry: &mut Registry) {
+    registry.register("day_thirteen_2016", DayThirteen2016::default());
 }
 
-pub fn solve_part_two() {
-    println!("{}", get_stretched_index_key("cuanljph", 64));
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use crate::checkpoint::{get_test_directory, Checkpointer};
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    #[test]
+    fn finds_the_example_key_index() {
+        assert_eq!(solve_example(&DayThirteen2016::default(), &["abc"], Part::One), Some(Answer::Integer(22728)));
+    }
+
+    #[test]
+    fn finds_the_first_example_stretched_key() {
+        assert_eq!(get_stretched_index_key("abc", 1, &Control::new()), Ok(Some(10)));
+    }
+
+    #[test]
+    fn rejects_a_goal_of_no_keys() {
+        let mut parameters = Parameters::new();
+        parameters.set("goal_num_keys", "0");
+        let error = DayThirteen2016::default().configure(&parameters).unwrap_err();
+        assert_eq!(error, "goal_num_keys must be at least 1");
+        assert_eq!(get_index_key("abc", 0, &Control::new()), Ok(None));
+    }
+
+    #[test]
+    fn stretches_the_hashes_of_long_salts() {
+        let mut bytes: [u8; 32] = [0; 32];
+        get_stretched_hash_bytes("a salt longer than the thirty two bytes of a hash", 0, &mut bytes);
+        assert!(bytes.iter().all(|byte| byte.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn resumes_the_stretched_search_where_it_stopped() {
+        let directory = get_test_directory("day_thirteen_2016");
+        let lines = vec![String::from("abc")];
+        let get_control = || {
+            let checkpointer = Checkpointer::new(&directory, 2016, 14, Part::Two, &lines, Duration::from_secs(3600));
+            Control::new().checkpoint(checkpointer)
+        };
+        let mut search = Search { index: 0, key_finder: KeyFinder::new(1) };
+        let mut bytes: [u8; 32] = [0; 32];
+        while search.index <= 10 {
+            get_stretched_hash_bytes("abc", search.index, &mut bytes);
+            search.key_finder.update(search.index, &bytes);
+            search.index += 1;
+        }
+        assert!(!search.key_finder.possible_keys.is_empty());
+        get_control().check_saving(search.index, &search).unwrap();
+        assert!(get_control().restore::<Search>().is_none());
+        Checkpointer::new(&directory, 2016, 14, Part::Two, &lines, Duration::from_secs(3600)).save(&search);
+        let restored = get_control().restore::<Search>().unwrap();
+        assert_eq!(restored.to_lines(), search.to_lines());
+        assert_eq!(get_stretched_index_key("abc", 1, &get_control()), Ok(Some(10)));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayThirteen2016::default());
+    }
+}
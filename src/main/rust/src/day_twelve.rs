@@ -1,50 +1,51 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use regex::Regex;
-use lazy_static::lazy_static;
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, List, PatternError};
 use queues::*;
 
-struct Node {
+pub struct Node {
     id: u64,
     neighbours_ids: Vec<u64>
 }
 
 impl Node {
-    pub fn from_string(string: &str) -> Option<Self> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"(\d+) <-> ((?:\d+(?:, )?)+)").unwrap();
-        }
-        let captures = RE.captures(string)?;
-        let node_id = captures.get(1)
-            .map(|re_match| re_match.as_str())
-            .and_then(|capture| capture.parse::<u64>().ok())?;
-        let neighbours_ids: Vec<u64> = captures.get(2)
-            .map(|re_match| re_match.as_str())
-            .map(|capture| {
-                capture
-                    .split(", ")
-                    .filter_map(|id_str| id_str.parse::<u64>().ok())
-                    .collect()
-            })?;
-        Some(Node{id: node_id, neighbours_ids})
+    pub fn from_string(string: &str) -> Result<Self, PatternError> {
+        parse_line!(string, r"(\d+) <-> ((?:\d+(?:, )?)+)" => |id: u64, neighbours: List<u64>| {
+            Node { id, neighbours_ids: neighbours.0 }
+        })
     }
 }
 
-fn read_nodes(path: &str) -> Vec<Node> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |lines| {
-                lines
-                    .into_iter()
-                    .filter_map(|line| Node::from_string(&line))
-                    .collect()
-            }
-        )
+/// Fails on pipes to programs that have no line of their own, even when parsing leniently, since
+/// the groups can't be followed through them.
+pub fn parse_nodes(lines: &[String], context: &mut ParseContext) -> Result<Vec<Node>, ParseError> {
+    let mut nodes: Vec<(usize, Node)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(node) = context.accept(index + 1, line, "'<id> <-> <id>, <id>, ...'", Node::from_string(line))? {
+            nodes.push((index, node));
+        }
+    }
+    let ids: HashSet<u64> = nodes.iter().map(|(_, node)| node.id).collect();
+    for (index, node) in &nodes {
+        if let Some(id) = node.neighbours_ids.iter().find(|id| !ids.contains(id)) {
+            let detail = format!("program {} has no line of its own", id);
+            return Err(context.error(index + 1, &lines[*index], "pipes to declared programs").with_detail(Some(detail)));
+        }
+    }
+    Ok(nodes.into_iter().map(|(_, node)| node).collect())
 }
 
-fn to_node_map(nodes: Vec<Node>) -> HashMap<u64, Node> {
+pub fn to_node_map(nodes: Vec<Node>) -> HashMap<u64, Node> {
     let mut map: HashMap<u64, Node> = HashMap::new();
     for node in nodes {
         map.insert(node.id, node);
@@ -52,22 +53,23 @@ fn to_node_map(nodes: Vec<Node>) -> HashMap<u64, Node> {
     map
 }
 
-fn get_connected_component(nodes_map: &HashMap<u64, Node>, root: u64) -> HashSet<u64> {
+pub fn get_connected_component(nodes_map: &HashMap<u64, Node>, root: u64) -> HashSet<u64> {
     let mut visited: HashSet<u64> = HashSet::new();
     let mut queue: Queue<u64> = queue![root];
     visited.insert(root);
-    while let Some(node) = queue.remove().ok() {
-        for neighbour in &nodes_map[&node].neighbours_ids {
+    while let Ok(node) = queue.remove() {
+        let neighbours_ids = nodes_map.get(&node).map_or(&[][..], |node| &node.neighbours_ids[..]);
+        for neighbour in neighbours_ids {
             if !visited.contains(neighbour) {
                 visited.insert(*neighbour);
-                queue.add(*neighbour);
+                let _ = queue.add(*neighbour);
             }
         }
     }
     visited
 }
 
-fn get_num_connected_components(nodes_map: &HashMap<u64, Node>) -> u64 {
+pub fn get_num_connected_components(nodes_map: &HashMap<u64, Node>) -> u64 {
     let mut num_connected_components: u64 = 0;
     let num_nodes = nodes_map.len();
     let mut visited: HashSet<u64> = HashSet::new();
@@ -75,9 +77,10 @@ fn get_num_connected_components(nodes_map: &HashMap<u64, Node>) -> u64 {
         let root: u64 = nodes_map
             .keys()
             .find(|node_id| !visited.contains(*node_id))
-            .map(|node_id| *node_id)
+            .copied()
             .unwrap_or(0);
         let current_visited = get_connected_component(nodes_map, root);
+        trace!(Debug, "group of program {} has {} programs", root, current_visited.len());
         for node in current_visited {
             visited.insert(node);
         }
@@ -86,16 +89,91 @@ fn get_num_connected_components(nodes_map: &HashMap<u64, Node>) -> u64 {
     num_connected_components
 }
 
-pub fn solve_part_one() {
-    let nodes = read_nodes("day_twelve.txt");
-    let nodes_map = to_node_map(nodes);
-    let connected_component_size = get_connected_component(&nodes_map, 0).len();
-    println!("{:?}", connected_component_size);
+/// Pipes go both ways, and programs without any pipe are connected to themselves.
+pub fn generate_pipes(random: &mut Random) -> Vec<String> {
+    let num_nodes = random.range(1, 300);
+    let mut neighbours: Vec<HashSet<u64>> = vec![HashSet::new(); num_nodes as usize];
+    for node in 0..num_nodes {
+        for _ in 0..random.range(0, 3) {
+            let other = random.range(0, num_nodes);
+            neighbours[node as usize].insert(other);
+            neighbours[other as usize].insert(node);
+        }
+    }
+    neighbours.iter()
+        .enumerate()
+        .map(|(node, node_neighbours)| {
+            let mut ids: Vec<u64> = node_neighbours.iter().copied().collect();
+            if ids.is_empty() {
+                ids.push(node as u64);
+            }
+            ids.sort();
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            format!("{} <-> {}", node, ids.join(", "))
+        })
+        .collect()
+}
+
+pub struct DayTwelve;
+
+impl Solution for DayTwelve {
+    type Input = HashMap<u64, Node>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        Ok(to_node_map(parse_nodes(lines, context)?))
+    }
+
+    fn part_one(&self, nodes_map: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_connected_component(nodes_map, 0).len()))
+    }
+
+    fn part_two(&self, nodes_map: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(Some(Answer::from(get_num_connected_components(nodes_map))))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_pipes(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_twelve", DayTwelve);
 }
 
-pub fn solve_part_two() {
-    let nodes = read_nodes("day_twelve.txt");
-    let nodes_map = to_node_map(nodes);
-    let num_connected_components = get_num_connected_components(&nodes_map);
-    println!("{:?}", num_connected_components);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, get_example_parse_error, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "0 <-> 2",
+        "1 <-> 1",
+        "2 <-> 0, 3, 4",
+        "3 <-> 2, 4",
+        "4 <-> 2, 3, 6",
+        "5 <-> 6",
+        "6 <-> 4, 5"
+    ];
+
+    #[test]
+    fn counts_the_programs_connected_to_zero() {
+        assert_eq!(solve_example(&DayTwelve, EXAMPLE, Part::One), Some(Answer::Integer(6)));
+    }
+
+    #[test]
+    fn counts_the_groups() {
+        assert_eq!(solve_example(&DayTwelve, EXAMPLE, Part::Two), Some(Answer::Integer(2)));
+    }
+
+    #[test]
+    fn rejects_pipes_to_undeclared_programs() {
+        let error = get_example_parse_error(&DayTwelve, &["0 <-> 2", "2 <-> 0, 6"]);
+        assert_eq!(error.to_string(), "<input>:2: expected pipes to declared programs, found '2 <-> 0, 6' (program 6 has no line of its own)");
+        assert_eq!(solve_example(&DayTwelve, &["1 <-> 1"], Part::One), Some(Answer::Integer(1)));
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTwelve);
+    }
 }
@@ -1,16 +1,27 @@
 use crate::utils::get_num_set_bits;
+use crate::registry::Registry;
+use crate::parameters::{Pair, Parameters};
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
 use std::collections::{HashMap, HashSet};
 use std::cell::RefCell;
 use queues::*;
 
-struct Map {
+/// Keeps the wall formula of the parameters within 64 bits.
+const MAX_COORDINATE: u64 = 1 << 24;
+
+pub struct Map {
     data: RefCell<HashMap<(u64, u64), bool>>,
     number: u64
 }
 
 impl Map {
 
-    fn new(number: u64) -> Self {
+    pub fn new(number: u64) -> Self {
         Map { data: RefCell::new(HashMap::new()), number }
     }
 
@@ -20,7 +31,7 @@ impl Map {
     }
 
     fn is_wall(&self, row: u64, col: u64) -> bool {
-        let opt = self.data.borrow().get(&(row, col)).map(|&value| value);
+        let opt = self.data.borrow().get(&(row, col)).copied();
         if let Some(value) = opt {
             value
         } else {
@@ -31,13 +42,19 @@ impl Map {
     }
 }
 
-fn get_min_steps(start: (u64, u64), destination: (u64, u64), map: &Map) -> u64 {
+/// Gives `None` when the walls close the start off from the destination.
+pub fn get_min_steps(start: (u64, u64), destination: (u64, u64), map: &Map, control: &Control) -> Result<Option<u64>, Interrupted> {
+    if start == destination {
+        return Ok(Some(0));
+    }
     let mut queue: Queue<((u64, u64), u64)> = Queue::new();
     let mut visited: HashSet<(u64, u64)> = HashSet::new();
     let directions: [(i64, i64); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
-    queue.add((start, 0));
+    let _ = queue.add((start, 0));
     visited.insert(start);
-    while let Some(((row, col), steps)) = queue.remove().ok() {
+    while let Ok(((row, col), steps)) = queue.remove() {
+        control.check(visited.len() as u64)?;
+        trace!(Trace, "expanding ({}, {}) {} steps away", row, col, steps);
         for &(row_offset, col_offset) in &directions {
             let signed_offseted_row = row as i64 + row_offset;
             let signed_offseted_col = col as i64 + col_offset;
@@ -46,25 +63,26 @@ fn get_min_steps(start: (u64, u64), destination: (u64, u64), map: &Map) -> u64 {
                 let offseted_col = signed_offseted_col as u64;
                 if !visited.contains(&(offseted_row, offseted_col)) && !map.is_wall(offseted_row, offseted_col) {
                     if (offseted_row, offseted_col) == destination {
-                        return steps + 1;
+                        trace!(Info, "reached the destination in {} steps after visiting {} locations", steps + 1, visited.len());
+                        return Ok(Some(steps + 1));
                     } else {
                         visited.insert((offseted_row, offseted_col));
-                        queue.add(((offseted_row, offseted_col), steps + 1));
+                        let _ = queue.add(((offseted_row, offseted_col), steps + 1));
                     }
                 }
             }
         }
     }
-    0
+    Ok(None)
 }
 
-fn get_num_destinations(start: (u64, u64), distance: u64, map: &Map) -> u64 {
+pub fn get_num_destinations(start: (u64, u64), distance: u64, map: &Map) -> u64 {
     let mut queue: Queue<((u64, u64), u64)> = Queue::new();
     let mut visited: HashSet<(u64, u64)> = HashSet::new();
     let directions: [(i64, i64); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
-    queue.add((start, 0));
+    let _ = queue.add((start, 0));
     visited.insert(start);
-    while let Some(((row, col), steps)) = queue.remove().ok() {
+    while let Ok(((row, col), steps)) = queue.remove() {
         if steps < distance {
             for &(row_offset, col_offset) in &directions {
                 let signed_offseted_row = row as i64 + row_offset;
@@ -74,7 +92,7 @@ fn get_num_destinations(start: (u64, u64), distance: u64, map: &Map) -> u64 {
                     let offseted_col = signed_offseted_col as u64;
                     if !visited.contains(&(offseted_row, offseted_col)) && !map.is_wall(offseted_row, offseted_col) {
                         visited.insert((offseted_row, offseted_col));
-                        queue.add(((offseted_row, offseted_col), steps + 1));
+                        let _ = queue.add(((offseted_row, offseted_col), steps + 1));
                     }
                 }
             }
@@ -83,13 +101,90 @@ fn get_num_destinations(start: (u64, u64), distance: u64, map: &Map) -> u64 {
     visited.len() as u64
 }
 
-pub fn solve_part_one() {
-    let map = Map::new(1350);
-    println!("{}", get_min_steps((1, 1), (39, 31), &map));
+pub fn generate_number(random: &mut Random) -> Vec<String> {
+    vec![random.range(1, 2000).to_string()]
+}
+
+pub struct DayTwelve2016 {
+    pub start: (u64, u64),
+    pub destination: (u64, u64),
+    pub distance: u64
 }
 
-pub fn solve_part_two() {
-    let map = Map::new(1350);
-    println!("{}", get_num_destinations((1, 1), 50, &map));
+impl Default for DayTwelve2016 {
+    fn default() -> Self {
+        DayTwelve2016 { start: (1, 1), destination: (39, 31), distance: 50 }
+    }
 }
 
+impl Solution for DayTwelve2016 {
+    type Input = Map;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        let number = context.parse_first(lines, "the office designer's favorite number", |line| line.parse::<u64>().ok())?;
+        Ok(Map::new(number))
+    }
+
+    fn part_one(&self, map: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        get_min_steps(self.start, self.destination, map, control)?
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::NoAnswer(String::from("the destination can't be reached")))
+    }
+
+    fn part_two(&self, map: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(Some(Answer::from(get_num_destinations(self.start, self.distance, map))))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_number(random))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        if let Some(Pair(row, col)) = parameters.get("start")? {
+            self.start = (row, col);
+        }
+        if let Some(Pair(row, col)) = parameters.get("destination")? {
+            self.destination = (row, col);
+        }
+        parameters.update("distance", &mut self.distance)?;
+        let coordinates = [self.start.0, self.start.1, self.destination.0, self.destination.1];
+        if coordinates.iter().any(|&coordinate| coordinate > MAX_COORDINATE) {
+            return Err(format!("start and destination must be within {} of the origin", MAX_COORDINATE));
+        }
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_twelve_2016", DayTwelve2016::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::StopReason;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    #[test]
+    fn walks_the_example_office() {
+        let solution = DayTwelve2016 { start: (1, 1), destination: (4, 7), distance: 0 };
+        assert_eq!(solve_example(&solution, &["10"], Part::One), Some(Answer::Integer(11)));
+    }
+
+    #[test]
+    fn reports_destinations_walled_off() {
+        let map = Map::new(10);
+        assert_eq!(get_min_steps((1, 1), (1, 1), &map, &Control::new()), Ok(Some(0)));
+        assert_eq!(get_min_steps((0, 2), (1, 1), &map, &Control::new()), Ok(None));
+        assert_eq!(get_min_steps((1, 1), (1000, 1000), &map, &Control::new()), Ok(None));
+        let map = Map::new(1350);
+        let interrupted = get_min_steps((1, 1), (1000, 1000), &map, &Control::new().timeout(Duration::from_secs(0))).unwrap_err();
+        assert_eq!(interrupted.reason, StopReason::TimedOut);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTwelve2016::default());
+    }
+}
@@ -1,22 +1,26 @@
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
 
 #[derive(Clone)]
-struct Interval {
+pub struct Interval {
     start: u64,
     end: u64
 }
 
 impl Interval {
-    fn from_string(string: &str) -> Option<Interval> {
-        let mut split = string.split('-');
-        let start = split.nth(0)?.parse::<u64>().ok()?;
-        let end = split.nth(0)?.parse::<u64>().ok()?;
-        Some(Interval { start, end })
+    fn from_string(string: &str) -> Result<Interval, PatternError> {
+        parse_line!(string, r"^(\d+)-(\d+)$" => Interval { start: u64, end: u64 })
     }
 }
 
-fn get_first_interval<'a>(intervals: &'a[Interval]) -> (Interval, &'a[Interval]) {
+fn get_first_interval(intervals: &[Interval]) -> (Interval, &[Interval]) {
     let mut final_end = intervals[0].end;
     let mut index: usize = 1;
     while index < intervals.len() && intervals[index].start <= final_end + 1 {
@@ -32,29 +36,21 @@ fn get_merged_intervals(intervals: &[Interval]) -> Vec<Interval> {
     let mut current_intervals = intervals;
     while !current_intervals.is_empty() {
         let (merged_interval, updated_intervals) = get_first_interval(current_intervals);
+        trace!(Debug, "blocks {}-{}, merged from {} ranges", merged_interval.start, merged_interval.end, current_intervals.len() - updated_intervals.len());
         merged_intervals.push(merged_interval);
         current_intervals = updated_intervals;
     }
     merged_intervals
 }
 
-fn get_final_intervals(intervals: &[Interval]) -> Vec<Interval> {
+pub fn get_final_intervals(intervals: &[Interval]) -> Vec<Interval> {
     let mut final_intervals = intervals.to_vec();
-    final_intervals.sort_by(|first, second| first.start.cmp(&second.start));
+    final_intervals.sort_by_key(|interval| interval.start);
     get_merged_intervals(&final_intervals)
 }
 
-fn read_intervals(path: &str) -> Vec<Interval> {
-    read_lines(path)
-        .map_or(
-            vec![],
-            |strings| {
-                strings
-                    .iter()
-                    .filter_map(|string| Interval::from_string(string))
-                    .collect()
-            }
-        )
+pub fn parse_intervals(lines: &[String], context: &mut ParseContext) -> Result<Vec<Interval>, ParseError> {
+    context.parse_lines(lines, "'<start>-<end>'", Interval::from_string)
 }
 
 fn get_num_blocked_ips(intervals: &[Interval]) -> u64 {
@@ -63,21 +59,64 @@ fn get_num_blocked_ips(intervals: &[Interval]) -> u64 {
         .sum()
 }
 
-fn get_num_allowed_ips(intervals: &[Interval]) -> u64 {
+pub fn get_num_allowed_ips(intervals: &[Interval]) -> u64 {
     4294967296u64 - get_num_blocked_ips(intervals)
 }
 
-pub fn solve_part_one() {
-    let intervals = read_intervals("day_twenty_2016.txt");
-    let intervals = get_final_intervals(&intervals);
-    let opt_answer = intervals.first().map(|interval| interval.end + 1);
-    if let Some(answer) = opt_answer {
-        println!("{}", answer);
+/// Ranges of every size, from single addresses to large parts of the address space, which may
+/// overlap, touch or leave gaps.
+pub fn generate_intervals(random: &mut Random) -> Vec<String> {
+    let max_ip: u64 = 4294967295;
+    (0..random.range(1, 200))
+        .map(|_| {
+            let start = if random.one_in(20) { 0 } else { random.range(0, max_ip + 1) };
+            let magnitude = random.range(1, 30);
+            let length = random.range(0, 1 << magnitude);
+            format!("{}-{}", start, std::cmp::min(start + length, max_ip))
+        })
+        .collect()
+}
+
+pub struct DayTwenty2016;
+
+impl Solution for DayTwenty2016 {
+    type Input = Vec<Interval>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        Ok(get_final_intervals(&parse_intervals(lines, context)?))
     }
+
+    fn part_one(&self, intervals: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        let answer = intervals.first().map_or(0, |interval| interval.end + 1);
+        Ok(Answer::from(answer))
+    }
+
+    fn part_two(&self, intervals: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(Some(Answer::from(get_num_allowed_ips(intervals))))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_intervals(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_twenty_2016", DayTwenty2016);
 }
 
-pub fn solve_part_two() {
-    let intervals = read_intervals("day_twenty_2016.txt");
-    let intervals = get_final_intervals(&intervals);
-    println!("{}", get_num_allowed_ips(&intervals));
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    #[test]
+    fn finds_the_lowest_example_address() {
+        let example = &["5-8", "0-2", "4-7"];
+        assert_eq!(solve_example(&DayTwenty2016, example, Part::One), Some(Answer::Integer(3)));
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTwenty2016);
+    }
+}
@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 use std::ops::{Index, IndexMut, Add};
-use crate::utils::read_lines;
-
-#[derive(Clone, Copy, PartialEq, Eq)]
+use crate::checkpoint::Checkpoint;
+use crate::registry::Registry;
+use crate::parameters::Parameters;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum NodeState {
     Clean,
     Weakened,
@@ -11,12 +19,31 @@ enum NodeState {
 }
 
 impl NodeState {
+    fn to_char(self) -> char {
+        match self {
+            NodeState::Clean => '.',
+            NodeState::Weakened => 'W',
+            NodeState::Infected => '#',
+            NodeState::Flagged => 'F'
+        }
+    }
+
+    fn from_char(chr: char) -> Option<Self> {
+        match chr {
+            '.' => Some(NodeState::Clean),
+            'W' => Some(NodeState::Weakened),
+            '#' => Some(NodeState::Infected),
+            'F' => Some(NodeState::Flagged),
+            _ => None
+        }
+    }
+
     fn get_next(&self) -> Self {
         match self {
-            &NodeState::Clean => NodeState::Weakened,
-            &NodeState::Weakened => NodeState::Infected,
-            &NodeState::Infected => NodeState::Flagged,
-            &NodeState::Flagged => NodeState::Clean
+            NodeState::Clean => NodeState::Weakened,
+            NodeState::Weakened => NodeState::Infected,
+            NodeState::Infected => NodeState::Flagged,
+            NodeState::Flagged => NodeState::Clean
         }
     }
 }
@@ -41,11 +68,6 @@ impl IndexMut<(i64, i64)> for MapNodeState {
 
 impl MapNodeState {
 
-    fn read_map(path: &str) -> Self {
-        let strings = read_lines(path).unwrap_or(vec![]);
-        Self::from_strings(&strings)
-    }
-
     fn from_strings(strings: &[String]) -> Self {
         let height = strings.len() as i64;
         let width = strings[0].len() as i64;
@@ -84,11 +106,6 @@ impl IndexMut<(i64, i64)> for Map {
 
 impl Map {
 
-    fn read_map(path: &str) -> Self {
-        let strings = read_lines(path).unwrap_or(vec![]);
-        Self::from_strings(&strings)
-    }
-
     fn from_strings(strings: &[String]) -> Self {
         let height = strings.len() as i64;
         let width = strings[0].len() as i64;
@@ -147,39 +164,58 @@ enum Direction {
 }
 
 impl Direction {
+    fn to_char(self) -> char {
+        match self {
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::Right => 'R',
+            Direction::Left => 'L'
+        }
+    }
+
+    fn from_char(chr: char) -> Option<Self> {
+        match chr {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'R' => Some(Direction::Right),
+            'L' => Some(Direction::Left),
+            _ => None
+        }
+    }
+
     fn get_turn_left(&self) -> Self {
         match self {
-            &Direction::Up => Direction::Left,
-            &Direction::Down => Direction::Right,
-            &Direction::Right => Direction::Up,
-            &Direction::Left => Direction::Down
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+            Direction::Left => Direction::Down
         }
     }
 
     fn get_turn_right(&self) -> Self {
         match self {
-            &Direction::Up => Direction::Right,
-            &Direction::Down => Direction::Left,
-            &Direction::Right => Direction::Down,
-            &Direction::Left => Direction::Up
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+            Direction::Right => Direction::Down,
+            Direction::Left => Direction::Up
         }
     }
 
     fn get_reversed(&self) -> Self {
         match self {
-            &Direction::Up => Direction::Down,
-            &Direction::Down => Direction::Up,
-            &Direction::Right => Direction::Left,
-            &Direction::Left => Direction::Right
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right
         }
     }
 
     fn get_coordinates(&self) -> Coordinates {
         match self {
-            &Direction::Up => Coordinates { x: 0, y: -1 },
-            &Direction::Down => Coordinates { x: 0, y: 1 },
-            &Direction::Right => Coordinates { x: 1, y: 0 },
-            &Direction::Left => Coordinates { x: -1, y: 0 }
+            Direction::Up => Coordinates { x: 0, y: -1 },
+            Direction::Down => Coordinates { x: 0, y: 1 },
+            Direction::Right => Coordinates { x: 1, y: 0 },
+            Direction::Left => Coordinates { x: -1, y: 0 }
         }
     }
 }
@@ -196,15 +232,14 @@ impl VirusCarrier {
     }
 
     fn update(&mut self, map: &mut Map) -> bool {
-        let mut has_infected = false;
-        if map[self.coordinates.into()] {
-            self.direction = self.direction.get_turn_right();
-            map[self.coordinates.into()] = false;
-            has_infected = false;
-        } else {
+        let has_infected = !map[self.coordinates.into()];
+        if has_infected {
             self.direction = self.direction.get_turn_left();
             map[self.coordinates.into()] = true;
-            has_infected = true;
+            trace!(Trace, "infects ({}, {})", self.coordinates.x, self.coordinates.y);
+        } else {
+            self.direction = self.direction.get_turn_right();
+            map[self.coordinates.into()] = false;
         }
         self.coordinates = self.coordinates + self.direction.get_coordinates();
         has_infected
@@ -219,34 +254,209 @@ impl VirusCarrier {
         };
         let next_node_state = map[self.coordinates.into()].get_next();
         let has_infected = next_node_state == NodeState::Infected;
+        trace!(Trace, "({}, {}) becomes {:?}", self.coordinates.x, self.coordinates.y, next_node_state);
         map[self.coordinates.into()] = next_node_state;
         self.coordinates = self.coordinates + self.direction.get_coordinates();
         has_infected
     }
 }
 
-pub fn solve_part_one(num_iter: u64) {
-    let mut map = Map::read_map("day_twentytwo.txt");
-    let mut virus_carrier = VirusCarrier::new();
-    let mut num_infections: u64 = 0;
-    for _ in 0..num_iter {
-        let has_infected = virus_carrier.update(&mut map);
-        if has_infected {
-            num_infections += 1;
+/// How far the evolved virus got: the bursts done so far, the infections they caused, where the
+/// carrier is and the states of the nodes that aren't clean.
+struct Evolution {
+    burst: u64,
+    num_infections: u64,
+    virus_carrier: VirusCarrier,
+    map: MapNodeState
+}
+
+impl Evolution {
+    fn new(strings: &[String]) -> Self {
+        Evolution { burst: 0, num_infections: 0, virus_carrier: VirusCarrier::new(), map: MapNodeState::from_strings(strings) }
+    }
+}
+
+/// Written as `<burst> <infections>`, then `<x> <y> <direction>` for the carrier, then
+/// `<x> <y> <state>` for every node that isn't clean.
+impl Checkpoint for Evolution {
+    fn to_lines(&self) -> Vec<String> {
+        let carrier = &self.virus_carrier;
+        let mut lines = vec![
+            format!("{} {}", self.burst, self.num_infections),
+            format!("{} {} {}", carrier.coordinates.x, carrier.coordinates.y, carrier.direction.to_char())
+        ];
+        let mut nodes: Vec<(&(i64, i64), &NodeState)> = self.map.data.iter()
+            .filter(|&(_, &state)| state != NodeState::Clean)
+            .collect();
+        nodes.sort_by_key(|&(&coordinates, _)| coordinates);
+        lines.extend(nodes.iter().map(|&(&(x, y), &state)| format!("{} {} {}", x, y, state.to_char())));
+        lines
+    }
+
+    fn from_lines(lines: &[String]) -> Option<Self> {
+        let mut counts = lines.first()?.split(' ');
+        let burst = counts.next()?.parse::<u64>().ok()?;
+        let num_infections = counts.next()?.parse::<u64>().ok()?;
+        let mut carrier = lines.get(1)?.split(' ');
+        let coordinates = Coordinates { x: carrier.next()?.parse().ok()?, y: carrier.next()?.parse().ok()? };
+        let direction = Direction::from_char(carrier.next()?.chars().next()?)?;
+        let mut map = MapNodeState { data: HashMap::new() };
+        for line in &lines[2..] {
+            let mut node = line.split(' ');
+            let x = node.next()?.parse::<i64>().ok()?;
+            let y = node.next()?.parse::<i64>().ok()?;
+            map[(x, y)] = NodeState::from_char(node.next()?.chars().next()?)?;
         }
+        Some(Evolution { burst, num_infections, virus_carrier: VirusCarrier { coordinates, direction }, map })
     }
-    println!("{}", num_infections);
 }
 
-pub fn solve_part_two(num_iter: u64) {
-    let mut map = MapNodeState::read_map("day_twentytwo.txt");
-    let mut virus_carrier = VirusCarrier::new();
-    let mut num_infections: u64 = 0;
-    for _ in 0..num_iter {
-        let has_infected = virus_carrier.update_node_states(&mut map);
-        if has_infected {
-            num_infections += 1;
+/// A square grid with an odd side, so that the carrier starts in the middle.
+pub fn generate_grid(random: &mut Random) -> Vec<String> {
+    let size = 2 * random.range(0, 12) + 1;
+    (0..size)
+        .map(|_| (0..size).map(|_| if random.one_in(3) { '#' } else { '.' }).collect())
+        .collect()
+}
+
+pub struct DayTwentyTwo {
+    pub part_one_bursts: u64,
+    pub part_two_bursts: u64
+}
+
+impl Default for DayTwentyTwo {
+    fn default() -> Self {
+        DayTwentyTwo { part_one_bursts: 10000, part_two_bursts: 10000000 }
+    }
+}
+
+impl Solution for DayTwentyTwo {
+    type Input = Vec<String>;
+
+    /// The rows have to make a rectangle, which is always checked since the carrier starts in its
+    /// middle.
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        let is_row = |line: &str| line.chars().all(|chr| chr == '#' || chr == '.');
+        let rows = context.parse_lines(lines, "a row of '#' and '.'", |line| {
+            if is_row(line) {
+                Some(line.to_string())
+            } else {
+                None
+            }
+        })?;
+        let width = rows.first()
+            .map(|row| row.len())
+            .ok_or_else(|| context.error(1, "", "a row of '#' and '.'"))?;
+        let ragged = lines.iter()
+            .enumerate()
+            .find(|(_, line)| !line.trim().is_empty() && is_row(line) && line.len() != width);
+        if let Some((index, line)) = ragged {
+            let detail = format!("the first row has {} nodes", width);
+            return Err(context.error(index + 1, line, "rows of the same length").with_detail(Some(detail)));
+        }
+        Ok(rows)
+    }
+
+    fn part_one(&self, strings: &Self::Input, control: &Control) -> Result<Answer, SolveError> {
+        let mut map = Map::from_strings(strings);
+        let mut virus_carrier = VirusCarrier::new();
+        let mut num_infections: u64 = 0;
+        for burst in 0..self.part_one_bursts {
+            control.check(burst)?;
+            let has_infected = virus_carrier.update(&mut map);
+            if has_infected {
+                num_infections += 1;
+            }
+        }
+        Ok(Answer::from(num_infections))
+    }
+
+    /// Goes on from a saved evolution unless it went past the bursts asked for.
+    fn part_two(&self, strings: &Self::Input, control: &Control) -> Result<Option<Answer>, SolveError> {
+        let mut evolution = control.restore::<Evolution>()
+            .filter(|evolution| evolution.burst <= self.part_two_bursts)
+            .unwrap_or_else(|| Evolution::new(strings));
+        while evolution.burst < self.part_two_bursts {
+            control.check_saving(evolution.burst, &evolution)?;
+            let has_infected = evolution.virus_carrier.update_node_states(&mut evolution.map);
+            if has_infected {
+                evolution.num_infections += 1;
+            }
+            evolution.burst += 1;
+        }
+        Ok(Some(Answer::from(evolution.num_infections)))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_grid(random))
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        parameters.update("part_one_bursts", &mut self.part_one_bursts)?;
+        parameters.update("part_two_bursts", &mut self.part_two_bursts)?;
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_twentytwo", DayTwentyTwo::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use crate::checkpoint::{get_test_directory, Checkpointer};
+    use crate::error::{Interrupted, StopReason};
+    use crate::solution::{assert_generated_inputs_parse, get_example_parse_error, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "..#",
+        "#..",
+        "..."
+    ];
+
+    #[test]
+    fn counts_the_infections_of_the_example() {
+        for &(bursts, infections) in &[(7, 5), (70, 41), (10000, 5587)] {
+            let solution = DayTwentyTwo { part_one_bursts: bursts, part_two_bursts: 0 };
+            assert_eq!(solve_example(&solution, EXAMPLE, Part::One), Some(Answer::Integer(infections)));
         }
     }
-    println!("{}", num_infections);
-}
\ No newline at end of file
+
+    #[test]
+    fn counts_the_evolved_infections_of_the_example() {
+        let solution = DayTwentyTwo { part_one_bursts: 0, part_two_bursts: 100 };
+        assert_eq!(solve_example(&solution, EXAMPLE, Part::Two), Some(Answer::Integer(26)));
+    }
+
+    #[test]
+    fn resumes_the_evolution_where_it_stopped() {
+        let directory = get_test_directory("day_twentytwo");
+        let solution = DayTwentyTwo { part_one_bursts: 0, part_two_bursts: 10000 };
+        let strings: Vec<String> = EXAMPLE.iter().map(|line| line.to_string()).collect();
+        let get_control = || {
+            let checkpointer = Checkpointer::new(&directory, 2017, 22, Part::Two, &strings, Duration::from_secs(3600));
+            Control::new().checkpoint(checkpointer)
+        };
+        let error = solution.part_two(&strings, &get_control().timeout(Duration::from_secs(0))).unwrap_err();
+        let evolution = get_control().restore::<Evolution>().unwrap();
+        assert_eq!(error, SolveError::Interrupted(Interrupted { reason: StopReason::TimedOut, progress: evolution.burst }));
+        assert!(evolution.burst > 0);
+        assert_eq!(solution.part_two(&strings, &get_control()), solution.part_two(&strings, &Control::new()));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn rejects_grids_that_are_not_rectangles() {
+        let error = get_example_parse_error(&DayTwentyTwo::default(), &["..#", "#.", "..."]);
+        assert_eq!(error.to_string(), "<input>:2: expected rows of the same length, found '#.' (the first row has 3 nodes)");
+        assert_eq!(get_example_parse_error(&DayTwentyTwo::default(), &[]).line, 1);
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTwentyTwo::default());
+    }
+}
@@ -1,9 +1,14 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use crate::utils::read_lines;
+use crate::registry::Registry;
+use crate::solution::{Answer, Solution};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::random::Random;
+use crate::utils::ParseContext;
+use crate::trace::trace;
+use crate::utils::pattern::{parse_line, PatternError};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Node {
+pub struct Node {
     x: u32,
     y: u32,
     size: u32,
@@ -13,18 +18,15 @@ struct Node {
 }
 
 impl Node {
-    fn from_string(string: &str) -> Option<Node> {
-        lazy_static! {
-            static ref REGEX: Regex = Regex::new(r"/dev/grid/node-x(\d+)-y(\d+)\s+(\d+)T\s+(\d+)T\s+(\d+)T\s+(\d+)%").unwrap();
-        }
-        let captures = REGEX.captures(string)?;
-        let x = captures.get(1)?.as_str().parse::<u32>().ok()?;
-        let y = captures.get(2)?.as_str().parse::<u32>().ok()?;
-        let size = captures.get(3)?.as_str().parse::<u32>().ok()?;
-        let used = captures.get(4)?.as_str().parse::<u32>().ok()?;
-        let available = captures.get(5)?.as_str().parse::<u32>().ok()?;
-        let use_per = captures.get(6)?.as_str().parse::<u32>().ok()?;
-        Some(Node { x, y, size, used, available, use_per })
+    fn from_string(string: &str) -> Result<Node, PatternError> {
+        parse_line!(string, r"/dev/grid/node-x(\d+)-y(\d+)\s+(\d+)T\s+(\d+)T\s+(\d+)T\s+(\d+)%" => Node {
+            x: u32,
+            y: u32,
+            size: u32,
+            used: u32,
+            available: u32,
+            use_per: u32
+        })
     }
 
     fn is_empty(&self) -> bool {
@@ -36,11 +38,12 @@ impl Node {
     }
 }
 
-fn get_num_viable_node_pairs(nodes: &[Node]) -> u64 {
+pub fn get_num_viable_node_pairs(nodes: &[Node]) -> u64 {
     let mut result: u64 = 0;
     for first_node in nodes {
         for second_node in nodes {
             if first_node != second_node && !first_node.is_empty() && second_node.can_fit(first_node) {
+                trace!(Trace, "({}, {}) fits on ({}, {})", first_node.x, first_node.y, second_node.x, second_node.y);
                 result += 1;
             }
         }
@@ -48,19 +51,98 @@ fn get_num_viable_node_pairs(nodes: &[Node]) -> u64 {
     result
 }
 
-fn read_nodes(path: &str) -> Vec<Node> {
-    read_lines(path)
-        .map_or(
-            vec![], 
-            |strings| {
-                strings.iter()
-                    .filter_map(|string| Node::from_string(string))
-                    .collect()
-            }
-        )
+fn is_header(string: &str) -> bool {
+    string.starts_with("root@") || string.starts_with("Filesystem")
+}
+
+pub fn parse_nodes(lines: &[String], context: &mut ParseContext) -> Result<Vec<Node>, ParseError> {
+    let mut nodes: Vec<Node> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() || is_header(line) {
+            continue;
+        }
+        let expected = "'/dev/grid/node-x<n>-y<n> <size>T <used>T <avail>T <use>%'";
+        if let Some(node) = context.accept(index + 1, line, expected, Node::from_string(line))? {
+            nodes.push(node);
+        }
+    }
+    Ok(nodes)
+}
+
+/// `df` output for a grid with one empty node, a few large nearly full nodes and the rest in between.
+pub fn generate_nodes(random: &mut Random) -> Vec<String> {
+    let width = random.range(2, 12);
+    let height = random.range(2, 12);
+    let empty = (random.range(0, width), random.range(0, height));
+    let mut lines = vec![
+        String::from("root@ebhq-gridcenter# df -h"),
+        String::from("Filesystem              Size  Used  Avail  Use%")
+    ];
+    for x in 0..width {
+        for y in 0..height {
+            let size = if random.one_in(10) { random.range(500, 511) } else { random.range(85, 95) };
+            let used = if (x, y) == empty { 0 } else { random.range(size * 7 / 10, size) };
+            lines.push(format!(
+                "{:<24}{:>4}T{:>5}T{:>6}T{:>5}%",
+                format!("/dev/grid/node-x{}-y{}", x, y),
+                size,
+                used,
+                size - used,
+                used * 100 / size
+            ));
+        }
+    }
+    lines
+}
+
+pub struct DayTwentyTwo2016;
+
+impl Solution for DayTwentyTwo2016 {
+    type Input = Vec<Node>;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+        parse_nodes(lines, context)
+    }
+
+    fn part_one(&self, nodes: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+        Ok(Answer::from(get_num_viable_node_pairs(nodes)))
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Some(generate_nodes(random))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("day_twentytwo_2016", DayTwentyTwo2016);
 }
 
-pub fn solve_part_one() {
-    let nodes = read_nodes("day_twentytwo_2016.txt");
-    println!("{}", get_num_viable_node_pairs(&nodes));
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{assert_generated_inputs_parse, solve_example, Part};
+
+    const EXAMPLE: &[&str] = &[
+        "root@ebhq-gridcenter# df -h",
+        "Filesystem            Size  Used  Avail  Use%",
+        "/dev/grid/node-x0-y0   10T    8T     2T   80%",
+        "/dev/grid/node-x0-y1   11T    6T     5T   54%",
+        "/dev/grid/node-x0-y2   32T   28T     4T   87%",
+        "/dev/grid/node-x1-y0    9T    7T     2T   77%",
+        "/dev/grid/node-x1-y1    8T    0T     8T    0%",
+        "/dev/grid/node-x1-y2   11T    7T     4T   63%",
+        "/dev/grid/node-x2-y0   10T    6T     4T   60%",
+        "/dev/grid/node-x2-y1    9T    8T     1T   88%",
+        "/dev/grid/node-x2-y2    9T    6T     3T   66%"
+    ];
+
+    #[test]
+    fn counts_the_viable_pairs_of_the_example_grid() {
+        assert_eq!(solve_example(&DayTwentyTwo2016, EXAMPLE, Part::One), Some(Answer::Integer(7)));
+    }
+
+    #[test]
+    fn generates_inputs_that_parse() {
+        assert_generated_inputs_parse(&DayTwentyTwo2016);
+    }
+}
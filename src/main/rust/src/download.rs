@@ -0,0 +1,241 @@
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+use crate::error::Error;
+
+/// Environment variable holding the base URL of the server inputs are downloaded from, when the
+/// configuration doesn't name one with `server`.
+pub const SERVER_VARIABLE: &str = "AOC_SERVER";
+
+/// Environment variable holding the session token the server knows the user by.
+pub const SESSION_VARIABLE: &str = "AOC_SESSION";
+
+pub const DEFAULT_CACHE_DIR: &str = "cache";
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An input fetched from `<server>/<year>/day/<day>/input` the first time it is read and cached in
+/// `path`; once cached it is read from there and never fetched again.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Download {
+    pub url: String,
+    pub path: String,
+    session: Option<String>
+}
+
+/// Leaves the session token out, so that it doesn't end up in logs.
+impl fmt::Debug for Download {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.debug_struct("Download")
+            .field("url", &self.url)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Cached inputs are named after the year and day, so that they don't clash and so that their
+/// answers can be told apart.
+pub fn get_cache_file(year: u32, day: u32) -> String {
+    format!("{}_day_{}.txt", year, day)
+}
+
+impl Download {
+    pub fn new(server: &str, session: Option<&str>, cache_dir: &str, year: u32, day: u32) -> Self {
+        Download {
+            url: format!("{}/{}/day/{}/input", server.trim_end_matches('/'), year, day),
+            path: Path::new(cache_dir).join(get_cache_file(year, day)).to_string_lossy().into_owned(),
+            session: session.map(|session| session.to_string())
+        }
+    }
+
+    /// Reads the cached input, downloading and caching it first when it isn't there yet. The input
+    /// is written aside and renamed, so that an interrupted download doesn't leave a truncated file
+    /// that would be taken for the input, and empty answers aren't cached at all.
+    pub fn read(&self) -> Result<String, Error> {
+        if Path::new(&self.path).exists() {
+            return fs::read_to_string(&self.path)
+                .map_err(|source| Error::Io { path: self.path.clone(), source });
+        }
+        let session = self.session.as_ref()
+            .ok_or_else(|| self.get_error(format!("no session token; set {}", SESSION_VARIABLE)))?;
+        let cookie = format!("session={}", session);
+        let body = get(&self.url, &[("Cookie", &cookie)]).map_err(|message| self.get_error(message))?;
+        if body.trim().is_empty() {
+            return Err(self.get_error(String::from("the server sent an empty input")));
+        }
+        if let Some(directory) = Path::new(&self.path).parent() {
+            fs::create_dir_all(directory)
+                .map_err(|source| Error::Write { path: directory.to_string_lossy().into_owned(), source })?;
+        }
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, &body)
+            .and_then(|_| fs::rename(&temporary, &self.path))
+            .map_err(|source| Error::Write { path: self.path.clone(), source })?;
+        Ok(body)
+    }
+
+    fn get_error(&self, message: String) -> Error {
+        Error::Download { url: self.url.clone(), message }
+    }
+}
+
+/// Splits a `http://host[:port][/path]` URL; other schemes aren't supported.
+fn parse_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url.strip_prefix("http://")
+        .ok_or_else(|| String::from("only http:// URLs are supported"))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/")
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| format!("invalid port '{}'", port))?),
+        None => (authority, 80)
+    };
+    if host.is_empty() {
+        return Err(String::from("no host"));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+pub(crate) fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes.windows(pattern.len()).position(|window| window == pattern)
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded: Vec<u8> = Vec::new();
+    loop {
+        let end = find(body, b"\r\n").ok_or_else(|| String::from("truncated chunk"))?;
+        let size = String::from_utf8_lossy(&body[..end]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| format!("invalid chunk size '{}'", size))?;
+        body = &body[end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if body.len() < size {
+            return Err(String::from("truncated chunk"));
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = body.get(size + 2..).unwrap_or(&[]);
+    }
+}
+
+/// Sends a GET request and reads the response until the server closes the connection, which it
+/// is asked to do, failing on any status but 200.
+pub fn get(url: &str, headers: &[(&str, &str)]) -> Result<String, String> {
+    let (host, port, path) = parse_url(url)?;
+    let mut stream = TcpStream::connect((host.as_str(), port)).map_err(|error| error.to_string())?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|error| error.to_string())?;
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", path, host);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(|error| error.to_string())?;
+    let mut response: Vec<u8> = Vec::new();
+    stream.read_to_end(&mut response).map_err(|error| error.to_string())?;
+    let end = find(&response, b"\r\n\r\n").ok_or_else(|| String::from("malformed response"))?;
+    let head = String::from_utf8_lossy(&response[..end]).into_owned();
+    let body = &response[end + 4..];
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or("");
+    let code = status.split_whitespace().nth(1).unwrap_or("");
+    if code != "200" {
+        return Err(format!("server answered '{}'", status));
+    }
+    let chunked = lines.any(|line| {
+        let line = line.to_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    let body = if chunked { decode_chunked(body)? } else { body.to_vec() };
+    String::from_utf8(body).map_err(|_| String::from("the input isn't UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Answers each of the connections it expects with the given response and hands back the
+    /// requests it got.
+    fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            responses.iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request: Vec<u8> = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while find(&request, b"\r\n\r\n").is_none() {
+                        let count = stream.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..count]);
+                    }
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+        (server, handle)
+    }
+
+    fn get_cache_dir(name: &str) -> String {
+        let directory = std::env::temp_dir().join(format!("aoc_download_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn downloads_and_caches_inputs() {
+        let (server, handle) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\n0: 3\n1"]);
+        let cache_dir = get_cache_dir("cache");
+        let download = Download::new(&format!("{}/", server), Some("token"), &cache_dir, 2017, 13);
+        assert_eq!(download.read().unwrap(), "0: 3\n1");
+        assert_eq!(download.read().unwrap(), "0: 3\n1");
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2017/day/13/input HTTP/1.1\r\n"), "{}", requests[0]);
+        assert!(requests[0].contains("Cookie: session=token\r\n"), "{}", requests[0]);
+        assert!(download.path.ends_with("2017_day_13.txt"));
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn reads_chunked_bodies() {
+        let (server, handle) = serve(vec![
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n"
+        ]);
+        assert_eq!(get(&server, &[]).unwrap(), "abcde");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn fails_without_caching_on_errors() {
+        let (server, handle) = serve(vec![
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\n\n"
+        ]);
+        let cache_dir = get_cache_dir("errors");
+        let download = Download::new(&server, Some("token"), &cache_dir, 2016, 15);
+        let error = download.read().unwrap_err().to_string();
+        assert!(error.ends_with("server answered 'HTTP/1.1 404 Not Found'"), "{}", error);
+        assert!(!Path::new(&download.path).exists());
+        let error = download.read().unwrap_err().to_string();
+        assert!(error.ends_with("the server sent an empty input"), "{}", error);
+        assert!(!Path::new(&download.path).exists());
+        handle.join().unwrap();
+        let download = Download::new(&server, None, &cache_dir, 2016, 15);
+        assert!(download.read().unwrap_err().to_string().contains(SESSION_VARIABLE));
+    }
+
+    #[test]
+    fn only_supports_plain_http() {
+        assert_eq!(parse_url("http://localhost:8080/aoc"), Ok((String::from("localhost"), 8080, String::from("/aoc"))));
+        assert_eq!(parse_url("http://example.com"), Ok((String::from("example.com"), 80, String::from("/"))));
+        assert!(parse_url("https://example.com").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::io;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    pub line: usize,
+    pub text: String,
+    pub expected: &'static str,
+    pub detail: Option<String>
+}
+
+impl ParseError {
+    pub fn new(file: Option<&str>, line: usize, text: &str, expected: &'static str) -> Self {
+        ParseError {
+            file: file.map(|file| file.to_string()),
+            line,
+            text: text.to_string(),
+            expected,
+            detail: None
+        }
+    }
+
+    pub fn with_detail(mut self, detail: Option<String>) -> Self {
+        self.detail = detail;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let file = self.file.as_deref().unwrap_or("<input>");
+        write!(formatter, "{}:{}: expected {}, found '{}'", file, self.line, self.expected, self.text)?;
+        match &self.detail {
+            Some(detail) => write!(formatter, " ({})", detail),
+            None => Ok(())
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Cancelled,
+    TimedOut
+}
+
+/// A solver stopped before finding its answer, after `progress` steps of its main loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interrupted {
+    pub reason: StopReason,
+    pub progress: u64
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            StopReason::Cancelled => "cancelled",
+            StopReason::TimedOut => "timed out"
+        };
+        write!(formatter, "{} after {} steps", reason, self.progress)
+    }
+}
+
+/// Why a part has no answer: the input doesn't parse, the solver was stopped, or the input parses
+/// but has no answer, like a machine that never gets to the state the puzzle asks about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Interrupted(Interrupted),
+    NoAnswer(String)
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(error) => write!(formatter, "{}", error),
+            SolveError::Interrupted(interrupted) => write!(formatter, "{}", interrupted),
+            SolveError::NoAnswer(reason) => write!(formatter, "no answer: {}", reason)
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> Self {
+        SolveError::Parse(error)
+    }
+}
+
+impl From<Interrupted> for SolveError {
+    fn from(interrupted: Interrupted) -> Self {
+        SolveError::Interrupted(interrupted)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io { path: String, source: io::Error },
+    Write { path: String, source: io::Error },
+    Download { url: String, message: String },
+    Parse(ParseError)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(formatter, "cannot read '{}': {}", path, source),
+            Error::Write { path, source } => write!(formatter, "cannot write '{}': {}", path, source),
+            Error::Download { url, message } => write!(formatter, "cannot download '{}': {}", url, message),
+            Error::Parse(error) => write!(formatter, "{}", error)
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
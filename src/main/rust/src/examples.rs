@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::Path;
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::config::Config;
+use crate::error::{Error, ParseError};
+use crate::input::InputSource;
+use crate::parameters::{Override, ParameterSettings};
+use crate::registry::get_registry;
+use crate::runner::{run_part, Outcome, RunOptions};
+use crate::solution::Part;
+
+pub const DEFAULT_FIXTURES_DIR: &str = "fixtures";
+
+/// An example of a puzzle description: the input, the answer the description gives for one part,
+/// and the parameters it takes when they differ from the ones of the puzzle, like a smaller
+/// number of steps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Example {
+    pub part: Part,
+    pub answer: String,
+    pub parameters: Vec<(String, String)>,
+    pub lines: Vec<String>
+}
+
+fn unescape(text: &str) -> String {
+    lazy_static! {
+        static ref TAG_RE: Regex = Regex::new(r"<[^>]*>").unwrap();
+    }
+    TAG_RE.replace_all(text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Reads the examples off a saved puzzle description page, which has an `<article>` per part. The
+/// input of a part is the first `<pre><code>` block of its article, or the one of part one when the
+/// second part goes on with the same example, and its answer is the last emphasized `<code>` of the
+/// article outside of the blocks. The first block isn't always the input, so the examples are
+/// meant to be looked over before they are kept.
+pub fn extract_examples(html: &str) -> Vec<Example> {
+    lazy_static! {
+        static ref ARTICLE_RE: Regex = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+        static ref BLOCK_RE: Regex = Regex::new(r"(?s)<pre><code>(.*?)</code></pre>").unwrap();
+        static ref ANSWER_RE: Regex = Regex::new(r"(?s)<code><em>(.*?)</em></code>|<em><code>(.*?)</code></em>").unwrap();
+    }
+    let mut examples: Vec<Example> = Vec::new();
+    let mut lines: Option<Vec<String>> = None;
+    for (article, part) in ARTICLE_RE.captures_iter(html).zip(&[Part::One, Part::Two]) {
+        let article = &article[1];
+        if let Some(block) = BLOCK_RE.captures(article) {
+            lines = Some(unescape(&block[1]).lines().map(|line| line.to_string()).collect());
+        }
+        let text = BLOCK_RE.replace_all(article, "");
+        let answer = ANSWER_RE.captures_iter(&text)
+            .last()
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .map(|answer| unescape(answer.as_str()));
+        if let (Some(lines), Some(answer)) = (&lines, answer) {
+            examples.push(Example { part: *part, answer, parameters: Vec::new(), lines: lines.clone() });
+        }
+    }
+    examples
+}
+
+/// Fixtures are text files holding examples one after the other, each made of `== part <part>`,
+/// `== answer <answer>` and any number of `== param <name>=<value>` lines followed by the lines of
+/// its input.
+pub fn format_fixture(examples: &[Example]) -> String {
+    let mut text = String::new();
+    for example in examples {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!("== part {}\n== answer {}\n", example.part, example.answer));
+        for (name, value) in &example.parameters {
+            text.push_str(&format!("== param {}={}\n", name, value));
+        }
+        for line in &example.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Parses a fixture; blank lines at the end of an example only separate it from the next one.
+pub fn parse_fixture(text: &str, file: Option<&str>) -> Result<Vec<Example>, ParseError> {
+    let mut examples: Vec<Example> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let error = |expected| ParseError::new(file, index + 1, line, expected);
+        if let Some(header) = line.strip_prefix("== ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match (key, examples.last_mut()) {
+                ("part", _) => {
+                    let part = value.parse::<u32>().ok()
+                        .and_then(Part::from_number)
+                        .ok_or_else(|| error("'== part <1|2>'"))?;
+                    examples.push(Example { part, answer: String::new(), parameters: Vec::new(), lines: Vec::new() });
+                },
+                ("answer", Some(example)) => example.answer = value.to_string(),
+                ("param", Some(example)) => {
+                    let parameter = value.parse::<Override>().ok()
+                        .filter(|parameter| parameter.puzzle.is_none())
+                        .ok_or_else(|| error("'== param <name>=<value>'"))?;
+                    example.parameters.push((parameter.name, parameter.value));
+                },
+                _ => return Err(error("'== part', '== answer' or '== param'"))
+            }
+            continue;
+        }
+        match examples.last_mut() {
+            Some(example) => example.lines.push(line.to_string()),
+            None if line.trim().is_empty() => {},
+            None => return Err(error("'== part <1|2>'"))
+        }
+    }
+    for example in &mut examples {
+        while example.lines.last().is_some_and(|line| line.trim().is_empty()) {
+            example.lines.pop();
+        }
+    }
+    Ok(examples)
+}
+
+pub fn get_fixture_path(directory: &str, year: u32, day: u32) -> String {
+    Path::new(directory).join(format!("{}_day_{}.txt", year, day)).to_string_lossy().into_owned()
+}
+
+/// The year and day of a fixture named like the ones `get_fixture_path` gives.
+pub fn get_fixture_puzzle(path: &str) -> Option<(u32, u32)> {
+    let name = Path::new(path).file_stem()?.to_str()?;
+    let (year, day) = name.split_once("_day_")?;
+    Some((year.parse::<u32>().ok()?, day.parse::<u32>().ok()?))
+}
+
+pub fn read_fixture(path: &str) -> Result<Vec<Example>, Error> {
+    let text = fs::read_to_string(path).map_err(|source| Error::Io { path: path.to_string(), source })?;
+    Ok(parse_fixture(&text, Some(path))?)
+}
+
+pub fn write_fixture(path: &str, examples: &[Example]) -> Result<(), Error> {
+    let get_error = |source| Error::Write { path: path.to_string(), source };
+    if let Some(directory) = Path::new(path).parent() {
+        fs::create_dir_all(directory).map_err(get_error)?;
+    }
+    fs::write(path, format_fixture(examples)).map_err(get_error)
+}
+
+/// Solves the example with the registered solver of the puzzle, parsing it strictly, and tells
+/// what went wrong when the answer isn't the one of the description. Examples of puzzles that
+/// aren't registered, because their year isn't enabled, are left alone.
+pub fn check_example(year: u32, day: u32, example: &Example) -> Result<(), String> {
+    let mut registry = get_registry();
+    if registry.find(year, day).is_none() {
+        return Ok(());
+    }
+    let overrides: Vec<Override> = example.parameters.iter()
+        .map(|(name, value)| Override { puzzle: Some((year, day)), name: name.clone(), value: value.clone() })
+        .collect();
+    registry.configure(&ParameterSettings::from_config(Config::new(), overrides))?;
+    let puzzle = registry.find(year, day).expect("the puzzle is registered");
+    let options = RunOptions { strict: true, ..RunOptions::default() };
+    let run = run_part(puzzle, example.part, &InputSource::Text(example.lines.join("\n")), &options);
+    match run.outcome {
+        Outcome::Solved(answer) if answer.to_string() == example.answer => Ok(()),
+        outcome => Err(format!(
+            "example of {} {} part {} gives {} instead of {}",
+            year, day, example.part, outcome, example.answer
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<main>
+<article class=\"day-desc\"><h2>--- Day 13: Packet Scanners ---</h2>
+<p>For example, suppose you've recorded the following:</p>
+<pre><code>0: 3
+1: 2
+4: 4
+6: 4
+</code></pre>
+<p>The severity of the whole trip is <code>0*3 + 6*4 = <em>24</em></code>, not <code>&lt;em&gt;</code>.</p>
+<p>In the example above, the trip severity is <code><em>24</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>1840</code>.</p>
+<article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>
+<p>The fewest number of picoseconds you would need to delay is <em><code>10</code></em>.</p>
+</article>
+</main>";
+
+    fn get_example(part: Part, answer: &str) -> Example {
+        let lines = ["0: 3", "1: 2", "4: 4", "6: 4"].iter().map(|line| line.to_string()).collect();
+        Example { part, answer: answer.to_string(), parameters: Vec::new(), lines }
+    }
+
+    #[test]
+    fn extracts_the_examples_of_both_parts() {
+        assert_eq!(extract_examples(PAGE), vec![get_example(Part::One, "24"), get_example(Part::Two, "10")]);
+    }
+
+    #[test]
+    fn parses_the_fixtures_it_formats() {
+        let mut example = get_example(Part::Two, "10");
+        example.parameters.push((String::from("delay"), String::from("0")));
+        let examples = vec![get_example(Part::One, "24"), example];
+        assert_eq!(parse_fixture(&format_fixture(&examples), None), Ok(examples));
+        let error = parse_fixture("0: 3\n", Some("2017_day_13.txt")).unwrap_err();
+        assert_eq!(error.to_string(), "2017_day_13.txt:1: expected '== part <1|2>', found '0: 3'");
+        assert!(parse_fixture("== part 1\n== param 2017.13.delay=0\n", None).is_err());
+        assert_eq!(get_fixture_puzzle("fixtures/2017_day_13.txt"), Some((2017, 13)));
+    }
+
+    #[test]
+    fn solves_the_example_fixtures() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_FIXTURES_DIR);
+        let mut failures: Vec<String> = Vec::new();
+        for entry in fs::read_dir(&directory).unwrap() {
+            let path = entry.unwrap().path().to_string_lossy().into_owned();
+            let (year, day) = get_fixture_puzzle(&path).unwrap_or_else(|| panic!("{} isn't named <year>_day_<day>.txt", path));
+            for example in read_fixture(&path).unwrap() {
+                if let Err(failure) = check_example(year, day, &example) {
+                    failures.push(failure);
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,118 @@
+use std::env;
+use std::path::Path;
+use crate::config::Config;
+use crate::download::{Download, DEFAULT_CACHE_DIR, SERVER_VARIABLE, SESSION_VARIABLE};
+use crate::error::Error;
+use crate::registry::{DefaultInput, Puzzle};
+use crate::utils::lines::{open_lines, stdin_lines, string_lines, Line, LineOptions};
+
+pub const DEFAULT_CONFIG_FILE: &str = "puzzles.toml";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    File(String),
+    Stdin,
+    Text(String),
+    Download(Download)
+}
+
+impl InputSource {
+    /// `-` stands for stdin, anything else is a file path.
+    pub fn from_argument(argument: &str) -> Self {
+        if argument == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(argument.to_string())
+        }
+    }
+
+    /// Streams the lines of the input, except for a download, which is cached whole first.
+    pub fn lines(&self, options: LineOptions) -> Result<Box<dyn Iterator<Item = Result<Line, Error>> + '_>, Error> {
+        Ok(match self {
+            InputSource::File(path) => Box::new(open_lines(path, options)?),
+            InputSource::Stdin => Box::new(stdin_lines(options)),
+            InputSource::Text(text) => Box::new(string_lines(text, options)),
+            InputSource::Download(download) => {
+                let lines: Vec<Result<Line, Error>> = string_lines(&download.read()?, options).collect();
+                Box::new(lines.into_iter())
+            }
+        })
+    }
+
+    pub fn read_lines(&self) -> Result<Vec<String>, Error> {
+        self.lines(LineOptions::default())?
+            .map(|line| line.map(|line| line.text))
+            .collect()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            InputSource::File(path) => Some(path),
+            InputSource::Stdin => Some("<stdin>"),
+            InputSource::Text(_) => None,
+            InputSource::Download(download) => Some(&download.path)
+        }
+    }
+}
+
+/// Where puzzle inputs come from when they aren't given on the command line: the input directory
+/// holding the default input files, and per-puzzle `[<year>.<day>]` sections of the configuration
+/// file with either an inline `input = "..."` or a `file = "..."` relative to the input directory.
+/// Inputs that are in neither place are downloaded from the `server`, when one is set, and cached
+/// in the `cache_dir`.
+pub struct InputSettings {
+    pub directory: String,
+    pub config: Config,
+    pub server: Option<String>,
+    pub session: Option<String>,
+    pub cache_dir: String
+}
+
+impl InputSettings {
+    pub fn new(directory: Option<&str>, config_path: Option<&str>) -> Result<Self, Error> {
+        let config_path = config_path
+            .map(|path| path.to_string())
+            .or_else(|| env::var("AOC_CONFIG").ok())
+            .unwrap_or_else(|| String::from(DEFAULT_CONFIG_FILE));
+        let config = Config::read(&config_path)?;
+        let directory = directory
+            .map(|directory| directory.to_string())
+            .or_else(|| env::var("AOC_INPUT_DIR").ok())
+            .or_else(|| config.get("", "input_dir").map(|directory| directory.to_string()))
+            .unwrap_or_else(|| String::from("."));
+        let server = env::var(SERVER_VARIABLE).ok()
+            .or_else(|| config.get("", "server").map(|server| server.to_string()));
+        let session = env::var(SESSION_VARIABLE).ok();
+        let cache_dir = env::var("AOC_CACHE_DIR").ok()
+            .or_else(|| config.get("", "cache_dir").map(|directory| directory.to_string()))
+            .unwrap_or_else(|| Path::new(&directory).join(DEFAULT_CACHE_DIR).to_string_lossy().into_owned());
+        Ok(InputSettings { directory, config, server, session, cache_dir })
+    }
+
+    fn get_path(&self, file: &str) -> String {
+        Path::new(&self.directory).join(file).to_string_lossy().into_owned()
+    }
+
+    pub fn resolve(&self, puzzle: &Puzzle) -> Option<InputSource> {
+        let section = format!("{}.{}", puzzle.year, puzzle.day);
+        if let Some(text) = self.config.get(&section, "input") {
+            return Some(InputSource::Text(text.to_string()));
+        }
+        if let Some(file) = self.config.get(&section, "file") {
+            return Some(InputSource::File(self.get_path(file)));
+        }
+        let path = match puzzle.default_input {
+            DefaultInput::File(file) => Some(self.get_path(file)),
+            DefaultInput::Text(text) => return Some(InputSource::Text(text.to_string())),
+            DefaultInput::None => None
+        };
+        match (path, &self.server) {
+            (Some(path), _) if Path::new(&path).exists() => Some(InputSource::File(path)),
+            (_, Some(server)) => {
+                let download = Download::new(server, self.session.as_deref(), &self.cache_dir, puzzle.year, puzzle.day);
+                Some(InputSource::Download(download))
+            },
+            (path, None) => path.map(InputSource::File)
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// Just enough JSON to write results out; objects keep their keys in insertion order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>)
+}
+
+impl Json {
+    pub fn object(entries: Vec<(&str, Json)>) -> Self {
+        Json::Object(entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(string: &str) -> Self {
+        Json::String(string.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, |value| value.into())
+    }
+}
+
+fn write_string(formatter: &mut fmt::Formatter, string: &str) -> fmt::Result {
+    write!(formatter, "\"")?;
+    for chr in string.chars() {
+        match chr {
+            '"' => write!(formatter, "\\\"")?,
+            '\\' => write!(formatter, "\\\\")?,
+            '\n' => write!(formatter, "\\n")?,
+            '\r' => write!(formatter, "\\r")?,
+            '\t' => write!(formatter, "\\t")?,
+            _ if (chr as u32) < 0x20 => write!(formatter, "\\u{:04x}", chr as u32)?,
+            _ => write!(formatter, "{}", chr)?
+        }
+    }
+    write!(formatter, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(formatter, "null"),
+            Json::Bool(value) => write!(formatter, "{}", value),
+            Json::Integer(value) => write!(formatter, "{}", value),
+            Json::Float(value) if value.is_finite() => write!(formatter, "{}", value),
+            Json::Float(_) => write!(formatter, "null"),
+            Json::String(string) => write_string(formatter, string),
+            Json::Array(values) => {
+                write!(formatter, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, ",")?;
+                    }
+                    write!(formatter, "{}", value)?;
+                }
+                write!(formatter, "]")
+            },
+            Json::Object(entries) => {
+                write!(formatter, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, ",")?;
+                    }
+                    write_string(formatter, key)?;
+                    write!(formatter, ":{}", value)?;
+                }
+                write!(formatter, "}}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_strings() {
+        let json = Json::from("a \"quoted\"\\path\n\u{1}");
+        assert_eq!(json.to_string(), r#""a \"quoted\"\\path\n\u0001""#);
+    }
+
+    #[test]
+    fn writes_nested_values() {
+        let json = Json::object(vec![
+            ("answer", Json::Integer(42)),
+            ("time", Json::Float(1.5)),
+            ("error", Json::from(None::<&str>)),
+            ("skipped", Json::Array(vec![Json::Bool(true), Json::Float(f64::NAN)]))
+        ]);
+        assert_eq!(json.to_string(), r#"{"answer":42,"time":1.5,"error":null,"skipped":[true,null]}"#);
+    }
+}
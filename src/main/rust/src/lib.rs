@@ -0,0 +1,55 @@
+pub mod utils;
+pub mod matrix;
+pub mod random;
+pub mod error;
+pub mod trace;
+pub mod checkpoint;
+pub mod control;
+pub mod solution;
+pub mod registry;
+pub mod catalogue;
+pub mod parameters;
+pub mod config;
+pub mod json;
+pub mod download;
+pub mod input;
+pub mod runner;
+pub mod batch;
+pub mod server;
+pub mod answers;
+pub mod examples;
+pub mod crosscheck;
+pub mod bench;
+pub mod baseline;
+#[cfg(feature = "year2016")]
+pub mod day_ten;
+#[cfg(feature = "year2016")]
+pub mod day_eleven;
+#[cfg(feature = "year2017")]
+pub mod day_twelve;
+#[cfg(feature = "year2017")]
+pub mod day_thirteen;
+#[cfg(feature = "year2017")]
+pub mod day_fourteen;
+#[cfg(feature = "year2017")]
+pub mod day_sixteen;
+#[cfg(feature = "year2017")]
+pub mod day_seventeen;
+#[cfg(feature = "year2017")]
+pub mod day_nineteen;
+#[cfg(feature = "year2017")]
+mod day_twentyone;
+#[cfg(feature = "year2016")]
+pub mod day_twelve_2016;
+#[cfg(feature = "year2016")]
+pub mod day_thirteen_2016;
+#[cfg(feature = "year2016")]
+pub mod day_fifteen_2016;
+#[cfg(feature = "year2016")]
+pub mod day_sixteen_2016;
+#[cfg(feature = "year2016")]
+pub mod day_twenty_2016;
+#[cfg(feature = "year2016")]
+pub mod day_twentytwo_2016;
+#[cfg(feature = "year2017")]
+pub mod day_twentytwo;
@@ -1,22 +1,647 @@
-mod utils;
-mod day_ten;
-mod day_eleven;
-mod day_twelve;
-mod day_thirteen;
-mod day_fourteen;
-mod day_sixteen;
-mod day_seventeen;
-mod day_nineteen;
-mod day_twentyone;
-mod day_twelve_2016;
-mod day_thirteen_2016;
-mod day_fifteen_2016;
-mod day_sixteen_2016;
-mod day_twenty_2016;
-mod day_twentytwo_2016;
-mod day_twentytwo;
-mod matrix;
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rust::answers::{format_checks, Answers};
+use rust::baseline::{compare, format_comparisons, get_revision, Baseline, DEFAULT_THRESHOLD};
+use rust::batch::{format_batch, get_input_files, run_batch};
+use rust::bench::{bench_part, format_benches, Bench, BenchOptions};
+use rust::catalogue::{find, format_catalogue};
+use rust::crosscheck::{format_reports, get_cross_checks, get_inputs, Report};
+use rust::examples::{extract_examples, get_fixture_path, write_fixture, DEFAULT_FIXTURES_DIR};
+use rust::input::{InputSettings, InputSource};
+use rust::parameters::{Override, ParameterSettings};
+use rust::random::Random;
+use rust::registry::{get_registry, Puzzle, Registry};
+use rust::runner::{format_runs, format_runs_json, run_all, run_part, Outcome, Run, RunOptions};
+use rust::server::{serve, ServeOptions, DEFAULT_ADDRESS, DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_INPUT_SIZE, DEFAULT_SOLVE_TIMEOUT};
+use rust::solution::Part;
+use rust::trace::{get_env_level, set_level, Level};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json
+}
+
+enum Command {
+    Solve { year: u32, day: u32, part: u32 },
+    All,
+    List,
+    Batch { year: u32, day: u32, directory: String },
+    Generate { year: u32, day: u32 },
+    Extract { year: u32, day: u32, page: String },
+    Serve,
+    CrossCheck,
+    Bench { puzzle: Option<(u32, u32)>, part: Option<u32> }
+}
+
+struct Arguments {
+    command: Command,
+    input: Option<InputSource>,
+    input_dir: Option<String>,
+    config: Option<String>,
+    answers: Option<String>,
+    strict: bool,
+    parallel: bool,
+    check: bool,
+    format: Format,
+    slow: Duration,
+    timeout: Option<Duration>,
+    progress: bool,
+    seed: Option<u64>,
+    seeds: u64,
+    output: Option<String>,
+    trace: Option<Level>,
+    bench: BenchOptions,
+    baseline: Option<String>,
+    save_baseline: bool,
+    compare_baseline: bool,
+    revision: Option<String>,
+    against: Option<String>,
+    threshold: u32,
+    parameters: Option<String>,
+    overrides: Vec<Override>,
+    checkpoint: Option<String>,
+    checkpoint_interval: Option<Duration>,
+    address: String,
+    max_input: usize,
+    max_connections: usize
+}
+
+const USAGE: &str = "usage:
+  rust <year> <day> <part> [--input <path|->] [--text <input>] [options]
+  rust all [--parallel] [--slow <ms>] [options]
+  rust list
+  rust batch <year> <day> <directory> [--parallel] [options]
+  rust generate <year> <day> [--seed <n>] [--output <path>]
+  rust extract <year> <day> <saved description page> [--output <path>]
+  rust serve [--address <host:port>] [--max-input <bytes>] [--max-connections <n>] [--timeout <ms>] [options]
+    answers POST /solve/<year>/<day>/<part> with the input as the body; parts time out after 10 s by default
+  rust crosscheck [--seeds <n>]
+  rust bench <all|<year> <day> [<part>]> [--iterations <n>] [--warmup <n>]
+    [--save-baseline] [--compare-baseline] [--baseline <file>] [--revision <rev>] [--against <rev>]
+    [--threshold <percent>]
+    saves under the checked out revision and compares with the revision saved last
+options: [--input-dir <dir>] [--config <file>] [--strict] [--format <text|json>]
+  [--check] [--answers <file>] [--timeout <ms>] [--progress]
+  [--trace <off|info|debug|trace>] (or the AOC_TRACE environment variable)
+  [--parameters <file>] [--param [<year>.<day>.]<name>=<value>]...
+  [--checkpoint <dir>] [--checkpoint-interval <s>]
+missing inputs are downloaded from the AOC_SERVER http:// URL with the AOC_SESSION token
+  and cached in AOC_CACHE_DIR (or the server and cache_dir configuration keys)";
+
+fn parse_number(string: &str, name: &str) -> Result<u32, String> {
+    string.parse::<u32>()
+        .map_err(|_| format!("invalid {} '{}'", name, string))
+}
+
+fn get_option_value(args: &[String], index: usize) -> Result<String, String> {
+    args.get(index + 1)
+        .cloned()
+        .ok_or_else(|| format!("missing value for {}", args[index]))
+}
+
+fn parse_arguments(args: &[String]) -> Result<Arguments, String> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut input: Option<InputSource> = None;
+    let mut input_dir: Option<String> = None;
+    let mut config: Option<String> = None;
+    let mut answers: Option<String> = None;
+    let mut strict = false;
+    let mut parallel = false;
+    let mut check = false;
+    let mut format = Format::Text;
+    let mut timeout: Option<Duration> = None;
+    let mut progress = false;
+    let mut seed: Option<u64> = None;
+    let mut seeds: u64 = 100;
+    let mut output: Option<String> = None;
+    let mut trace: Option<Level> = None;
+    let mut bench = BenchOptions::default();
+    let mut baseline: Option<String> = None;
+    let mut save_baseline = false;
+    let mut compare_baseline = false;
+    let mut revision: Option<String> = None;
+    let mut against: Option<String> = None;
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut parameters: Option<String> = None;
+    let mut overrides: Vec<Override> = Vec::new();
+    let mut checkpoint: Option<String> = None;
+    let mut checkpoint_interval: Option<Duration> = None;
+    let mut address = String::from(DEFAULT_ADDRESS);
+    let mut max_input = DEFAULT_MAX_INPUT_SIZE;
+    let mut max_connections = DEFAULT_MAX_CONNECTIONS;
+    let mut slow = Duration::from_secs(1);
+    let mut index: usize = 0;
+    while index < args.len() {
+        let arg = args[index].as_str();
+        match arg {
+            "--input" | "-i" => {
+                input = Some(InputSource::from_argument(&get_option_value(args, index)?));
+                index += 1;
+            },
+            "--text" => {
+                input = Some(InputSource::Text(get_option_value(args, index)?));
+                index += 1;
+            },
+            "--input-dir" => {
+                input_dir = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--config" => {
+                config = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--answers" => {
+                answers = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--format" => {
+                format = match get_option_value(args, index)?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format '{}'; expected text or json", other))
+                };
+                index += 1;
+            },
+            "--slow" => {
+                let millis = parse_number(&get_option_value(args, index)?, "--slow")?;
+                slow = Duration::from_millis(millis as u64);
+                index += 1;
+            },
+            "--timeout" => {
+                let millis = parse_number(&get_option_value(args, index)?, "--timeout")?;
+                timeout = Some(Duration::from_millis(millis as u64));
+                index += 1;
+            },
+            "--seed" => {
+                let value = get_option_value(args, index)?;
+                seed = Some(value.parse::<u64>().map_err(|_| format!("invalid --seed '{}'", value))?);
+                index += 1;
+            },
+            "--seeds" => {
+                seeds = parse_number(&get_option_value(args, index)?, "--seeds")? as u64;
+                index += 1;
+            },
+            "--iterations" => {
+                bench.iterations = parse_number(&get_option_value(args, index)?, "--iterations")? as usize;
+                index += 1;
+            },
+            "--warmup" => {
+                bench.warmup = parse_number(&get_option_value(args, index)?, "--warmup")? as usize;
+                index += 1;
+            },
+            "--baseline" => {
+                baseline = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--revision" => {
+                revision = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--against" => {
+                against = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--threshold" => {
+                threshold = parse_number(&get_option_value(args, index)?, "--threshold")?;
+                index += 1;
+            },
+            "--parameters" => {
+                parameters = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--param" | "-p" => {
+                overrides.push(get_option_value(args, index)?.parse::<Override>()?);
+                index += 1;
+            },
+            "--checkpoint" => {
+                checkpoint = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--checkpoint-interval" => {
+                let seconds = parse_number(&get_option_value(args, index)?, "--checkpoint-interval")?;
+                checkpoint_interval = Some(Duration::from_secs(seconds as u64));
+                index += 1;
+            },
+            "--address" => {
+                address = get_option_value(args, index)?;
+                index += 1;
+            },
+            "--max-input" => {
+                max_input = parse_number(&get_option_value(args, index)?, "--max-input")? as usize;
+                index += 1;
+            },
+            "--max-connections" => {
+                max_connections = parse_number(&get_option_value(args, index)?, "--max-connections")? as usize;
+                index += 1;
+            },
+            "--trace" => {
+                trace = Some(get_option_value(args, index)?.parse::<Level>()?);
+                index += 1;
+            },
+            "--output" | "-o" => {
+                output = Some(get_option_value(args, index)?);
+                index += 1;
+            },
+            "--strict" => strict = true,
+            "--progress" => progress = true,
+            "--parallel" => parallel = true,
+            "--check" => check = true,
+            "--save-baseline" => save_baseline = true,
+            "--compare-baseline" => compare_baseline = true,
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option '{}'", arg)),
+            _ => positional.push(arg)
+        }
+        index += 1;
+    }
+    let command = match positional.as_slice() {
+        ["all"] => Command::All,
+        ["list"] => Command::List,
+        ["serve"] => Command::Serve,
+        ["batch", year, day, directory] => Command::Batch {
+            year: parse_number(year, "year")?,
+            day: parse_number(day, "day")?,
+            directory: directory.to_string()
+        },
+        ["crosscheck"] => Command::CrossCheck,
+        ["bench", "all"] => Command::Bench { puzzle: None, part: None },
+        ["bench", year, day] => Command::Bench {
+            puzzle: Some((parse_number(year, "year")?, parse_number(day, "day")?)),
+            part: None
+        },
+        ["bench", year, day, part] => Command::Bench {
+            puzzle: Some((parse_number(year, "year")?, parse_number(day, "day")?)),
+            part: Some(parse_number(part, "part")?)
+        },
+        ["generate", year, day] => Command::Generate {
+            year: parse_number(year, "year")?,
+            day: parse_number(day, "day")?
+        },
+        ["extract", year, day, page] => Command::Extract {
+            year: parse_number(year, "year")?,
+            day: parse_number(day, "day")?,
+            page: page.to_string()
+        },
+        [year, day, part] => Command::Solve {
+            year: parse_number(year, "year")?,
+            day: parse_number(day, "day")?,
+            part: parse_number(part, "part")?
+        },
+        _ => return Err(String::from("expected 'all', 'list', 'serve', 'batch', 'crosscheck', 'bench', 'generate' with year and day, 'extract' with year, day and page, or year, day and part"))
+    };
+    let puzzle = match command {
+        Command::Solve { year, day, .. } | Command::Generate { year, day } | Command::Batch { year, day, .. } => Some((year, day)),
+        Command::Bench { puzzle, .. } => puzzle,
+        _ => None
+    };
+    for parameter in &mut overrides {
+        if parameter.puzzle.is_none() {
+            parameter.puzzle = Some(puzzle.ok_or_else(|| {
+                format!("--param {} doesn't name a puzzle; use <year>.<day>.{}", parameter.name, parameter.name)
+            })?);
+        }
+    }
+    if bench.iterations == 0 {
+        return Err(String::from("--iterations must be at least 1"));
+    }
+    if check && format == Format::Json {
+        return Err(String::from("--check only reports in the text format"));
+    }
+    Ok(Arguments {
+        command,
+        input,
+        input_dir,
+        config,
+        answers,
+        strict,
+        parallel,
+        check,
+        format,
+        slow,
+        timeout,
+        progress,
+        seed,
+        seeds,
+        output,
+        trace,
+        bench,
+        baseline,
+        save_baseline,
+        compare_baseline,
+        revision,
+        against,
+        threshold,
+        parameters,
+        overrides,
+        checkpoint,
+        checkpoint_interval,
+        address,
+        max_input,
+        max_connections
+    })
+}
+
+fn get_available_puzzles(registry: &Registry) -> String {
+    registry.puzzles().iter()
+        .map(|puzzle| format!("  {} {} {} ({})", puzzle.year, puzzle.day, puzzle.title, puzzle.name))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn get_run_options(arguments: &Arguments) -> RunOptions {
+    let mut options = RunOptions {
+        strict: arguments.strict,
+        timeout: arguments.timeout,
+        checkpoint_dir: arguments.checkpoint.clone(),
+        checkpoint_interval: arguments.checkpoint_interval,
+        ..RunOptions::default()
+    };
+    if arguments.progress {
+        options.progress = Some(Box::new(|puzzle, part, steps| {
+            eprintln!("progress: {} {} part {}: {} steps", puzzle.year, puzzle.day, part, steps);
+        }));
+    }
+    options
+}
+
+fn find_puzzle(registry: &Registry, year: u32, day: u32) -> Result<&Puzzle, String> {
+    registry.find(year, day)
+        .ok_or_else(|| {
+            format!(
+                "no puzzle for year {}, day {}; available puzzles:\n{}",
+                year,
+                day,
+                get_available_puzzles(registry)
+            )
+        })
+}
+
+/// The puzzles with the parameters of the parameters file and of the command line.
+fn get_configured_registry(arguments: &Arguments, settings: &InputSettings) -> Result<Registry, String> {
+    let parameters = ParameterSettings::read(arguments.parameters.as_deref(), settings, arguments.overrides.clone())
+        .map_err(|error| error.to_string())?;
+    let mut registry = get_registry();
+    registry.configure(&parameters)?;
+    Ok(registry)
+}
+
+/// The JSON format has the warnings in each run.
+fn print_warnings(runs: &[Run]) {
+    for run in runs {
+        for warning in &run.warnings {
+            eprintln!("warning: puzzle {} {} part {}: {}", run.year, run.day, run.part, warning);
+        }
+    }
+}
+
+fn solve(arguments: &Arguments, settings: &InputSettings, year: u32, day: u32, part: u32) -> Result<(), String> {
+    let registry = get_configured_registry(arguments, settings)?;
+    let puzzle = find_puzzle(&registry, year, day)?;
+    let part = Part::from_number(part)
+        .ok_or_else(|| format!("invalid part {}; expected 1 or 2", part))?;
+    let input = arguments.input.clone()
+        .or_else(|| settings.resolve(puzzle))
+        .ok_or_else(|| format!("puzzle {} {} has no default input; use --input", year, day))?;
+    let run = run_part(puzzle, part, &input, &get_run_options(arguments));
+    if arguments.format == Format::Json {
+        println!("{}", run.to_json());
+    } else {
+        for skipped in &run.skipped {
+            eprintln!("warning: skipped {}", skipped);
+        }
+        print_warnings(std::slice::from_ref(&run));
+    }
+    if arguments.check {
+        return check(arguments, settings, &[run]);
+    }
+    match run.outcome {
+        Outcome::Solved(answer) => {
+            if arguments.format == Format::Text {
+                println!("{}", answer);
+            }
+            Ok(())
+        },
+        Outcome::Unsolved => Err(format!("part {} of puzzle {} {} is not solved", part, year, day)),
+        Outcome::Failed(error) => Err(error),
+        Outcome::Interrupted(interrupted) => Err(format!("part {} of puzzle {} {} {}", part, year, day, interrupted))
+    }
+}
+
+/// Runs the puzzle on every file in the directory; fails when any of them couldn't be solved.
+fn batch(arguments: &Arguments, settings: &InputSettings, year: u32, day: u32, directory: &str) -> Result<(), String> {
+    let registry = get_configured_registry(arguments, settings)?;
+    let puzzle = find_puzzle(&registry, year, day)?;
+    let inputs = get_input_files(directory).map_err(|error| error.to_string())?;
+    if inputs.is_empty() {
+        return Err(format!("no input files in '{}'", directory));
+    }
+    let runs = run_batch(puzzle, &inputs, &get_run_options(arguments), arguments.parallel);
+    match arguments.format {
+        Format::Text => println!("{}", format_batch(&runs)),
+        Format::Json => println!("{}", format_runs_json(&runs))
+    }
+    if runs.iter().any(|run| matches!(run.outcome, Outcome::Failed(_) | Outcome::Interrupted(_))) {
+        Err(String::from("some inputs couldn't be solved"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Without a seed the generator is seeded from the clock, and the seed is printed so that the input
+/// can be made again.
+fn generate(arguments: &Arguments, year: u32, day: u32) -> Result<(), String> {
+    let registry = get_registry();
+    let puzzle = find_puzzle(&registry, year, day)?;
+    let seed = arguments.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as u64);
+        eprintln!("seed: {}", seed);
+        seed
+    });
+    let lines = puzzle.generate(&mut Random::new(seed))
+        .ok_or_else(|| format!("puzzle {} {} has no input generator", year, day))?;
+    let text = lines.join("\n") + "\n";
+    match &arguments.output {
+        Some(path) => fs::write(path, text).map_err(|error| format!("cannot write '{}': {}", path, error)),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Writes the examples of a saved puzzle description as a fixture, by default in the fixtures
+/// directory of the input directory, where the tests of a checkout find them.
+fn extract(arguments: &Arguments, settings: &InputSettings, year: u32, day: u32, page: &str) -> Result<(), String> {
+    find(year, day).ok_or_else(|| format!("no puzzle for year {}, day {} in the catalogue", year, day))?;
+    let html = fs::read_to_string(page).map_err(|error| format!("cannot read '{}': {}", page, error))?;
+    let examples = extract_examples(&html);
+    if examples.is_empty() {
+        return Err(format!("no examples with answers in '{}'", page));
+    }
+    let path = arguments.output.clone().unwrap_or_else(|| {
+        let directory = Path::new(&settings.directory).join(DEFAULT_FIXTURES_DIR);
+        get_fixture_path(&directory.to_string_lossy(), year, day)
+    });
+    write_fixture(&path, &examples).map_err(|error| error.to_string())?;
+    eprintln!("wrote {} examples to {}; add '== param <name>=<value>' lines to those using other values than the puzzle", examples.len(), path);
+    Ok(())
+}
+
+/// Benchmarks the given part, both parts of the given puzzle, or every puzzle that has an input;
+/// when benchmarking every puzzle, parts that fail are reported and left out.
+fn bench(arguments: &Arguments, settings: &InputSettings, puzzle: Option<(u32, u32)>, part: Option<u32>) -> Result<(), String> {
+    let registry = get_configured_registry(arguments, settings)?;
+    let puzzles: Vec<&Puzzle> = match puzzle {
+        Some((year, day)) => vec![find_puzzle(&registry, year, day)?],
+        None => registry.puzzles().iter().collect()
+    };
+    let parts: Vec<Part> = match part {
+        Some(number) => vec![Part::from_number(number).ok_or_else(|| format!("invalid part {}; expected 1 or 2", number))?],
+        None => vec![Part::One, Part::Two]
+    };
+    let puzzles_given = puzzle.is_some();
+    let options = get_run_options(arguments);
+    let mut benches: Vec<Bench> = Vec::new();
+    for puzzle in puzzles {
+        let input = match arguments.input.clone().or_else(|| settings.resolve(puzzle)) {
+            Some(input) => input,
+            None if puzzles_given => {
+                return Err(format!("puzzle {} {} has no default input; use --input", puzzle.year, puzzle.day));
+            },
+            None => continue
+        };
+        for &part in &parts {
+            match bench_part(puzzle, part, &input, &options, &arguments.bench) {
+                Ok(Some(bench)) => benches.push(bench),
+                Ok(None) => (),
+                Err(error) if !puzzles_given => eprintln!("warning: {}", error),
+                Err(error) => return Err(error)
+            }
+        }
+    }
+    println!("{}", format_benches(&benches));
+    if arguments.save_baseline || arguments.compare_baseline {
+        return update_baseline(arguments, settings, &benches);
+    }
+    Ok(())
+}
+
+/// Compares the benchmarks with the ones saved for `--against`, or else the revision saved last,
+/// then saves them for `--revision`, or else the checked out one; fails when any part got slower
+/// than the threshold.
+fn update_baseline(arguments: &Arguments, settings: &InputSettings, benches: &[Bench]) -> Result<(), String> {
+    let mut baseline = Baseline::read(arguments.baseline.as_deref(), settings)
+        .map_err(|error| error.to_string())?;
+    let mut result = Ok(());
+    if arguments.compare_baseline {
+        match arguments.against.as_deref().or_else(|| baseline.latest()) {
+            Some(against) => {
+                let (table, passed) = format_comparisons(&compare(benches, &baseline, against), arguments.threshold);
+                println!("\ncompared with {} in {}:\n{}", against, baseline.path(), table);
+                if !passed {
+                    result = Err(format!("some parts got more than {}% slower than at {}", arguments.threshold, against));
+                }
+            },
+            None => println!("\nnothing saved in {} to compare with yet", baseline.path())
+        }
+    }
+    if arguments.save_baseline {
+        let revision = arguments.revision.clone()
+            .or_else(get_revision)
+            .ok_or_else(|| String::from("cannot tell the git revision; use --revision"))?;
+        baseline.record(&revision, benches);
+        baseline.write().map_err(|error| error.to_string())?;
+        println!("\nsaved as {} in {}", revision, baseline.path());
+    }
+    result
+}
+
+/// Fails when the reference and fast algorithms of any check disagree.
+fn cross_check(arguments: &Arguments, settings: &InputSettings) -> Result<(), String> {
+    let registry = get_registry();
+    let reports: Vec<Report> = get_cross_checks().iter()
+        .map(|check| check.run(&get_inputs(&registry, settings, check, arguments.seeds)))
+        .collect();
+    println!("{}", format_reports(&reports));
+    if reports.iter().all(|report| report.disagreements.is_empty()) {
+        Ok(())
+    } else {
+        Err(String::from("some reference and fast algorithms disagree"))
+    }
+}
+
+fn check(arguments: &Arguments, settings: &InputSettings, runs: &[Run]) -> Result<(), String> {
+    let answers = Answers::read(arguments.answers.as_deref(), settings)
+        .map_err(|error| error.to_string())?;
+    let (table, passed) = format_checks(runs, &answers);
+    println!("{}", table);
+    if passed {
+        Ok(())
+    } else {
+        Err(String::from("some answers don't match the expected ones"))
+    }
+}
+
+fn run(arguments: &Arguments) -> Result<(), String> {
+    if let Some(level) = arguments.trace.or(get_env_level()?) {
+        set_level(level);
+    }
+    let settings = InputSettings::new(arguments.input_dir.as_deref(), arguments.config.as_deref())
+        .map_err(|error| error.to_string())?;
+    match arguments.command {
+        Command::Solve { year, day, part } => solve(arguments, &settings, year, day, part),
+        Command::Generate { year, day } => generate(arguments, year, day),
+        Command::Extract { year, day, ref page } => extract(arguments, &settings, year, day, page),
+        Command::Serve => {
+            let registry = get_configured_registry(arguments, &settings)?;
+            let listener = TcpListener::bind(&arguments.address)
+                .map_err(|error| format!("cannot listen on {}: {}", arguments.address, error))?;
+            let options = ServeOptions {
+                strict: arguments.strict,
+                max_input_size: arguments.max_input,
+                timeout: arguments.timeout.unwrap_or(DEFAULT_SOLVE_TIMEOUT),
+                max_connections: arguments.max_connections,
+                ..ServeOptions::default()
+            };
+            eprintln!("listening on {}", arguments.address);
+            serve(listener, registry, options);
+            Ok(())
+        },
+        Command::CrossCheck => cross_check(arguments, &settings),
+        Command::Batch { year, day, ref directory } => batch(arguments, &settings, year, day, directory),
+        Command::List => {
+            println!("{}", format_catalogue(&get_registry()));
+            Ok(())
+        },
+        Command::Bench { puzzle, part } => bench(arguments, &settings, puzzle, part),
+        Command::All => {
+            let runs = run_all(&get_configured_registry(arguments, &settings)?, &settings, &get_run_options(arguments), arguments.parallel);
+            if arguments.format == Format::Text {
+                print_warnings(&runs);
+            }
+            if arguments.check {
+                return check(arguments, &settings, &runs);
+            }
+            match arguments.format {
+                Format::Text => println!("{}", format_runs(&runs, arguments.slow)),
+                Format::Json => println!("{}", format_runs_json(&runs))
+            }
+            Ok(())
+        }
+    }
+}
 
 fn main() {
-    day_twentytwo::solve_part_two(10000000);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = parse_arguments(&args)
+        .map_err(|error| format!("{}\n{}", error, USAGE))
+        .and_then(|arguments| run(&arguments));
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use crate::catalogue::Metadata;
+use crate::config::Config;
+use crate::error::Error;
+use crate::input::InputSettings;
+
+pub const DEFAULT_PARAMETERS_FILE: &str = "parameters.toml";
+
+/// The parameters given to one puzzle; the ones that aren't given keep the values the official
+/// puzzle asks for, which the solvers start from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Parameters {
+    values: HashMap<String, String>
+}
+
+impl Parameters {
+    pub fn new() -> Self {
+        Parameters::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    /// Parses the parameter when it is given.
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.values.get(name)
+            .map(|value| value.parse::<T>().map_err(|_| format!("invalid value '{}' for parameter {}", value, name)))
+            .transpose()
+    }
+
+    /// Replaces the value with the parameter when it is given.
+    pub fn update<T: FromStr>(&self, name: &str, value: &mut T) -> Result<(), String> {
+        if let Some(parameter) = self.get(name)? {
+            *value = parameter;
+        }
+        Ok(())
+    }
+
+    /// Fails on parameters the puzzle doesn't take, which are most likely misspelled.
+    pub fn check(&self, metadata: &Metadata) -> Result<(), String> {
+        let mut names: Vec<&String> = self.values.keys().collect();
+        names.sort();
+        for name in names {
+            if !metadata.parameters.iter().any(|parameter| parameter.name == name) {
+                let known: Vec<&str> = metadata.parameters.iter().map(|parameter| parameter.name).collect();
+                let known = if known.is_empty() { String::from("none") } else { known.join(", ") };
+                return Err(format!(
+                    "puzzle {} {} has no parameter {}; it takes {}",
+                    metadata.year, metadata.day, name, known
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A row and column, or any other pair of values, written as `<first>,<second>`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pair<T>(pub T, pub T);
+
+impl<T: FromStr> FromStr for Pair<T> {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (first, second) = string.split_once(',').ok_or(())?;
+        Ok(Pair(first.trim().parse::<T>().map_err(|_| ())?, second.trim().parse::<T>().map_err(|_| ())?))
+    }
+}
+
+/// A parameter given on the command line as `<name>=<value>` for the puzzle being run, or as
+/// `<year>.<day>.<name>=<value>` for any puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Override {
+    pub puzzle: Option<(u32, u32)>,
+    pub name: String,
+    pub value: String
+}
+
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = || format!("invalid parameter '{}'; expected [<year>.<day>.]<name>=<value>", string);
+        let (key, value) = string.split_once('=').ok_or_else(error)?;
+        let parts: Vec<&str> = key.trim().split('.').collect();
+        let (puzzle, name) = match parts.as_slice() {
+            [name] => (None, *name),
+            [year, day, name] => {
+                let year = year.parse::<u32>().map_err(|_| error())?;
+                let day = day.parse::<u32>().map_err(|_| error())?;
+                (Some((year, day)), *name)
+            },
+            _ => return Err(error())
+        };
+        if name.is_empty() {
+            return Err(error());
+        }
+        Ok(Override { puzzle, name: name.to_string(), value: value.trim().to_string() })
+    }
+}
+
+/// The parameters file, in the configuration format with a `[<year>.<day>]` section of parameters
+/// per puzzle, along with the parameters given on the command line, which take precedence.
+pub struct ParameterSettings {
+    config: Config,
+    overrides: Vec<Override>
+}
+
+impl ParameterSettings {
+    /// The parameters file is given on the command line, named by `parameters` in the
+    /// configuration, or the default file in the input directory.
+    pub fn read(path: Option<&str>, settings: &InputSettings, overrides: Vec<Override>) -> Result<Self, Error> {
+        let path = path
+            .or_else(|| settings.config.get("", "parameters"))
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| Path::new(&settings.directory).join(DEFAULT_PARAMETERS_FILE).to_string_lossy().into_owned());
+        Ok(ParameterSettings { config: Config::read(&path)?, overrides })
+    }
+
+    pub fn from_config(config: Config, overrides: Vec<Override>) -> Self {
+        ParameterSettings { config, overrides }
+    }
+
+    pub fn overrides(&self) -> &[Override] {
+        &self.overrides
+    }
+
+    pub fn get(&self, metadata: &Metadata) -> Parameters {
+        let section = format!("{}.{}", metadata.year, metadata.day);
+        let mut parameters = Parameters::new();
+        for (name, value) in self.config.entries(&section) {
+            if !is_input_key(name) {
+                parameters.set(name, value);
+            }
+        }
+        for parameter in &self.overrides {
+            if parameter.puzzle == Some((metadata.year, metadata.day)) {
+                parameters.set(&parameter.name, &parameter.value);
+            }
+        }
+        parameters
+    }
+}
+
+/// The keys telling where the input comes from, left out when the parameters file is the
+/// configuration file.
+fn is_input_key(name: &str) -> bool {
+    name == "input" || name == "file"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalogue::find;
+    use crate::registry::Registry;
+
+    #[test]
+    fn parses_overrides() {
+        let parameter = "2017.22.part_two_bursts=100".parse::<Override>().unwrap();
+        assert_eq!(parameter, Override { puzzle: Some((2017, 22)), name: String::from("part_two_bursts"), value: String::from("100") });
+        assert_eq!("distance = 10".parse::<Override>().unwrap().puzzle, None);
+        assert!("distance".parse::<Override>().is_err());
+        assert!("2017.distance=10".parse::<Override>().is_err());
+    }
+
+    #[test]
+    fn takes_overrides_over_the_file() {
+        let config = Config::from_string("[2016.13]\ndistance = 20\ndestination = \"7,4\"\n", None).unwrap();
+        let overrides = vec!["2016.13.distance=30".parse::<Override>().unwrap()];
+        let parameters = ParameterSettings::from_config(config, overrides).get(find(2016, 13).unwrap());
+        assert_eq!(parameters.get::<u64>("distance"), Ok(Some(30)));
+        assert_eq!(parameters.get::<Pair<u64>>("destination"), Ok(Some(Pair(7, 4))));
+        assert_eq!(parameters.get::<u64>("start"), Ok(None));
+        assert!(parameters.check(find(2016, 13).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_and_invalid_parameters() {
+        let mut parameters = Parameters::new();
+        parameters.set("bursts", "10");
+        let error = parameters.check(find(2017, 22).unwrap()).unwrap_err();
+        assert_eq!(error, "puzzle 2017 22 has no parameter bursts; it takes part_one_bursts, part_two_bursts");
+        parameters.set("part_one_bursts", "many");
+        let mut bursts: u64 = 10000;
+        assert!(parameters.update("part_one_bursts", &mut bursts).is_err());
+        assert_eq!(bursts, 10000);
+    }
+
+    #[test]
+    fn rejects_overrides_for_puzzles_not_registered() {
+        let overrides = vec!["2017.22.part_two_bursts=100".parse::<Override>().unwrap()];
+        let settings = ParameterSettings::from_config(Config::from_string("", None).unwrap(), overrides);
+        let error = Registry::default().configure(&settings).unwrap_err();
+        assert_eq!(error, "--param 2017.22.part_two_bursts: there is no puzzle 2017 22");
+    }
+}
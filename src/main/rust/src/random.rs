@@ -0,0 +1,80 @@
+/// A small seeded pseudo random generator (SplitMix64), good enough to make up puzzle inputs and
+/// the same on every platform for a given seed.
+pub struct Random {
+    state: u64
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+
+    /// A number in `low..high`; the small bias of the modulo doesn't matter for inputs.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        low + self.next_u64() % (high - low)
+    }
+
+    /// True about once every `times` calls.
+    pub fn one_in(&mut self, times: u64) -> bool {
+        self.range(0, times) == 0
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.range(0, index as u64 + 1) as usize;
+            items.swap(index, other);
+        }
+    }
+
+    pub fn word(&mut self, length: usize) -> String {
+        (0..length)
+            .map(|_| (b'a' + self.range(0, 26) as u8) as char)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_for_the_same_seed() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+        assert_ne!(Random::new(1).next_u64(), Random::new(2).next_u64());
+    }
+
+    #[test]
+    fn stays_in_range() {
+        let mut random = Random::new(7);
+        for _ in 0..1000 {
+            let value = random.range(3, 10);
+            assert!((3..10).contains(&value));
+        }
+    }
+
+    #[test]
+    fn shuffles_without_losing_items() {
+        let mut random = Random::new(3);
+        let mut items: Vec<u32> = (0..50).collect();
+        random.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::catalogue::{find, find_module};
+use crate::control::Control;
+use crate::error::SolveError;
+use crate::parameters::ParameterSettings;
+use crate::random::Random;
+use crate::solution::{Answer, Part, Solution, Solver, Timed};
+use crate::utils::ParseContext;
+
+#[derive(Copy, Clone)]
+pub enum DefaultInput {
+    File(&'static str),
+    Text(&'static str),
+    None
+}
+
+/// A registered solver, under the official year and day of the puzzle it solves; `name` is the
+/// name of its module.
+pub struct Puzzle {
+    pub year: u32,
+    pub day: u32,
+    pub name: &'static str,
+    pub title: &'static str,
+    pub default_input: DefaultInput,
+    solver: Box<dyn Solver + Send + Sync>
+}
+
+impl Puzzle {
+    pub fn solve(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Option<Answer>, SolveError> {
+        self.solver.solve(lines, part, context, control)
+    }
+
+    pub fn solve_timed(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Timed, SolveError> {
+        self.solver.solve_timed(lines, part, context, control)
+    }
+
+    pub fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        self.solver.generate(random)
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    puzzles: Vec<Puzzle>
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry { puzzles: Vec::new() }
+    }
+
+    /// Registers the solution of a module under the puzzle the catalogue says it solves. Panics
+    /// when the module isn't in the catalogue, which is a mistake in the code rather than in the
+    /// input.
+    pub fn register<S: Solution + Send + Sync + 'static>(&mut self, name: &'static str, solution: S) {
+        let metadata = find_module(name)
+            .unwrap_or_else(|| panic!("module {} isn't in the catalogue", name));
+        self.puzzles.push(Puzzle {
+            year: metadata.year,
+            day: metadata.day,
+            name,
+            title: metadata.title,
+            default_input: metadata.input,
+            solver: Box::new(solution)
+        });
+        self.puzzles.sort_by_key(|puzzle| (puzzle.year, puzzle.day));
+    }
+
+    /// Gives every puzzle its parameters, failing on the first one it doesn't take and on
+    /// parameters given on the command line to puzzles that aren't registered.
+    pub fn configure(&mut self, settings: &ParameterSettings) -> Result<(), String> {
+        for parameter in settings.overrides() {
+            if let Some((year, day)) = parameter.puzzle {
+                if self.find(year, day).is_none() {
+                    return Err(format!("--param {}.{}.{}: there is no puzzle {} {}", year, day, parameter.name, year, day));
+                }
+            }
+        }
+        for puzzle in &mut self.puzzles {
+            let metadata = find(puzzle.year, puzzle.day).expect("registered puzzles are in the catalogue");
+            let parameters = settings.get(metadata);
+            parameters.check(metadata)?;
+            puzzle.solver.configure(&parameters)
+                .map_err(|error| format!("puzzle {} {}: {}", puzzle.year, puzzle.day, error))?;
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, year: u32, day: u32) -> Option<&Puzzle> {
+        self.puzzles.iter().find(|puzzle| puzzle.year == year && puzzle.day == day)
+    }
+
+    pub fn puzzles(&self) -> &[Puzzle] {
+        &self.puzzles
+    }
+}
+
+#[cfg(feature = "year2017")]
+fn register_2017(registry: &mut Registry) {
+    use crate::{day_twelve, day_thirteen, day_fourteen, day_sixteen, day_seventeen, day_nineteen, day_twentytwo};
+    day_twelve::register(registry);
+    day_thirteen::register(registry);
+    day_fourteen::register(registry);
+    day_sixteen::register(registry);
+    day_seventeen::register(registry);
+    day_nineteen::register(registry);
+    day_twentytwo::register(registry);
+}
+
+#[cfg(feature = "year2016")]
+fn register_2016(registry: &mut Registry) {
+    use crate::{day_ten, day_eleven, day_twelve_2016, day_thirteen_2016, day_fifteen_2016, day_sixteen_2016, day_twenty_2016, day_twentytwo_2016};
+    day_ten::register(registry);
+    day_eleven::register(registry);
+    day_twelve_2016::register(registry);
+    day_thirteen_2016::register(registry);
+    day_fifteen_2016::register(registry);
+    day_sixteen_2016::register(registry);
+    day_twenty_2016::register(registry);
+    day_twentytwo_2016::register(registry);
+}
+
+/// Holds the puzzles of the years enabled by the `year2016` and `year2017` features.
+pub fn get_registry() -> Registry {
+    #[allow(unused_mut)]
+    let mut registry = Registry::new();
+    #[cfg(feature = "year2017")]
+    register_2017(&mut registry);
+    #[cfg(feature = "year2016")]
+    register_2016(&mut registry);
+    registry
+}
@@ -0,0 +1,289 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::checkpoint::{Checkpointer, DEFAULT_CHECKPOINT_INTERVAL};
+use crate::control::{CancelToken, Control};
+use crate::error::{Interrupted, ParseError, SolveError};
+use crate::input::{InputSettings, InputSource};
+use crate::json::Json;
+use crate::registry::{Puzzle, Registry};
+use crate::solution::{Answer, Part};
+use crate::utils::{format_table, ParseContext};
+
+pub enum Outcome {
+    Solved(Answer),
+    Unsolved,
+    Failed(String),
+    Interrupted(Interrupted)
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Solved(answer) => write!(formatter, "{}", answer),
+            Outcome::Unsolved => write!(formatter, "-"),
+            Outcome::Failed(error) => write!(formatter, "error: {}", error),
+            Outcome::Interrupted(interrupted) => write!(formatter, "{}", interrupted)
+        }
+    }
+}
+
+pub type ProgressCallback = dyn Fn(&Puzzle, Part, u64) + Send + Sync;
+
+/// How parts are run: `timeout` limits each part on its own, `token` cancels all of them, and
+/// `progress` is told every now and then how many steps a long running part went through.
+/// Long simulations save their state in `checkpoint_dir`, when given, every `checkpoint_interval`
+/// and when they are stopped, and go on from it on the next run.
+#[derive(Default)]
+pub struct RunOptions {
+    pub strict: bool,
+    pub timeout: Option<Duration>,
+    pub token: CancelToken,
+    pub progress: Option<Box<ProgressCallback>>,
+    pub checkpoint_dir: Option<String>,
+    pub checkpoint_interval: Option<Duration>
+}
+
+impl RunOptions {
+    pub(crate) fn get_control<'a>(&'a self, puzzle: &'a Puzzle, part: Part) -> Control<'a> {
+        let mut control = Control::with_token(self.token.clone());
+        if let Some(timeout) = self.timeout {
+            control = control.timeout(timeout);
+        }
+        if let Some(progress) = &self.progress {
+            control = control.progress(move |steps| progress(puzzle, part, steps));
+        }
+        control
+    }
+
+    fn get_checkpointer(&self, puzzle: &Puzzle, part: Part, lines: &[String]) -> Option<Checkpointer> {
+        let interval = self.checkpoint_interval.unwrap_or(DEFAULT_CHECKPOINT_INTERVAL);
+        self.checkpoint_dir.as_ref()
+            .map(|directory| Checkpointer::new(directory, puzzle.year, puzzle.day, part, lines, interval))
+    }
+}
+
+pub struct Run {
+    pub year: u32,
+    pub day: u32,
+    pub part: Part,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    pub skipped: Vec<ParseError>,
+    pub warnings: Vec<String>,
+    pub input: Option<InputSource>
+}
+
+/// The message a panic was raised with, when it has one.
+pub(crate) fn get_panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("no message"))
+}
+
+/// Reads the input and solves one part, timing the parsing and solving but not the reading. The
+/// checkpoint of a part is removed once it is solved, and a solver that panics fails the part
+/// instead of taking the other parts down with it.
+pub fn run_part(puzzle: &Puzzle, part: Part, input: &InputSource, options: &RunOptions) -> Run {
+    let mut run = Run {
+        year: puzzle.year,
+        day: puzzle.day,
+        part,
+        outcome: Outcome::Unsolved,
+        duration: Duration::from_secs(0),
+        skipped: Vec::new(),
+        warnings: Vec::new(),
+        input: Some(input.clone())
+    };
+    let lines = match input.read_lines() {
+        Ok(lines) => lines,
+        Err(error) => {
+            run.outcome = Outcome::Failed(error.to_string());
+            return run;
+        }
+    };
+    let mut context = ParseContext::new(input.name(), options.strict);
+    let mut control = options.get_control(puzzle, part);
+    if let Some(checkpointer) = options.get_checkpointer(puzzle, part, &lines) {
+        control = control.checkpoint(checkpointer);
+    }
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| puzzle.solve(&lines, part, &mut context, &control)));
+    run.duration = start.elapsed();
+    if let Ok(Ok(Some(_))) = result {
+        control.clear_checkpoint();
+    }
+    run.warnings = control.take_warnings();
+    let result = match result {
+        Ok(result) => result,
+        Err(payload) => {
+            run.outcome = Outcome::Failed(format!("the solver panicked: {}", get_panic_message(payload.as_ref())));
+            run.skipped = context.skipped;
+            return run;
+        }
+    };
+    run.outcome = match result {
+        Ok(Some(answer)) => Outcome::Solved(answer),
+        Ok(None) => Outcome::Unsolved,
+        Err(SolveError::Parse(error)) => Outcome::Failed(error.to_string()),
+        Err(SolveError::Interrupted(interrupted)) => Outcome::Interrupted(interrupted),
+        Err(error @ SolveError::NoAnswer(_)) => Outcome::Failed(error.to_string())
+    };
+    run.skipped = context.skipped;
+    run
+}
+
+fn run_puzzle_part(puzzle: &Puzzle, part: Part, settings: &InputSettings, options: &RunOptions) -> Run {
+    match settings.resolve(puzzle) {
+        Some(input) => run_part(puzzle, part, &input, options),
+        None => Run {
+            year: puzzle.year,
+            day: puzzle.day,
+            part,
+            outcome: Outcome::Failed(String::from("no input")),
+            duration: Duration::from_secs(0),
+            skipped: Vec::new(),
+            warnings: Vec::new(),
+            input: None
+        }
+    }
+}
+
+/// Runs the jobs one after the other or each on its own thread, keeping their order, and leaves
+/// out the parts that a puzzle doesn't solve. A solver that panics only fails its own job, since
+/// `run_part` catches the panic.
+pub(crate) fn run_jobs<J: Sync, F: Fn(&J) -> Run + Sync>(jobs: &[J], parallel: bool, run: F) -> Vec<Run> {
+    let runs: Vec<Run> = if parallel {
+        let run = &run;
+        thread::scope(|scope| {
+            let handles: Vec<_> = jobs.iter()
+                .map(|job| scope.spawn(move || run(job)))
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    } else {
+        jobs.iter().map(run).collect()
+    };
+    runs.into_iter()
+        .filter(|run| !matches!(run.outcome, Outcome::Unsolved))
+        .collect()
+}
+
+/// Runs both parts of every registered puzzle, one after the other or each on its own thread.
+/// Parts that a puzzle doesn't solve are left out.
+pub fn run_all(registry: &Registry, settings: &InputSettings, options: &RunOptions, parallel: bool) -> Vec<Run> {
+    let jobs: Vec<(&Puzzle, Part)> = registry.puzzles().iter()
+        .flat_map(|puzzle| vec![(puzzle, Part::One), (puzzle, Part::Two)])
+        .collect();
+    run_jobs(&jobs, parallel, |&(puzzle, part)| run_puzzle_part(puzzle, part, settings, options))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn get_parse_error_json(error: &ParseError) -> Json {
+    Json::object(vec![
+        ("file", Json::from(error.file.as_deref())),
+        ("line", Json::Integer(error.line as i64)),
+        ("text", Json::from(error.text.as_str())),
+        ("expected", Json::from(error.expected)),
+        ("detail", Json::from(error.detail.as_deref())),
+        ("message", Json::String(error.to_string()))
+    ])
+}
+
+impl Run {
+    pub fn to_json(&self) -> Json {
+        let (answer, error, progress) = match &self.outcome {
+            Outcome::Solved(Answer::Integer(value)) => (Json::Integer(*value), Json::Null, Json::Null),
+            Outcome::Solved(Answer::Text(text)) => (Json::from(text.as_str()), Json::Null, Json::Null),
+            Outcome::Unsolved => (Json::Null, Json::Null, Json::Null),
+            Outcome::Failed(error) => (Json::Null, Json::from(error.as_str()), Json::Null),
+            Outcome::Interrupted(interrupted) => {
+                (Json::Null, Json::String(interrupted.to_string()), Json::Integer(interrupted.progress as i64))
+            }
+        };
+        Json::object(vec![
+            ("puzzle", Json::String(format!("{}.{}", self.year, self.day))),
+            ("year", Json::Integer(self.year as i64)),
+            ("day", Json::Integer(self.day as i64)),
+            ("part", Json::Integer(self.part.number() as i64)),
+            ("input", Json::from(self.input.as_ref().and_then(|input| input.name()))),
+            ("answer", answer),
+            ("error", error),
+            ("progress", progress),
+            ("duration_ms", Json::Float(self.duration.as_secs_f64() * 1000.0)),
+            ("diagnostics", Json::Array(self.skipped.iter().map(get_parse_error_json).collect())),
+            ("warnings", Json::Array(self.warnings.iter().map(|warning| Json::from(warning.as_str())).collect()))
+        ])
+    }
+}
+
+/// One JSON object per line, so that every run can be read on its own.
+pub fn format_runs_json(runs: &[Run]) -> String {
+    runs.iter()
+        .map(|run| run.to_json().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Runs that took at least `slow` are marked so they stand out in the table.
+pub fn format_runs(runs: &[Run], slow: Duration) -> String {
+    let rows: Vec<Vec<String>> = runs.iter()
+        .map(|run| {
+            let marker = if run.duration >= slow { "SLOW" } else { "" };
+            vec![
+                run.year.to_string(),
+                run.day.to_string(),
+                run.part.to_string(),
+                run.outcome.to_string(),
+                format_duration(run.duration),
+                String::from(marker)
+            ]
+        })
+        .collect();
+    format_table(&["year", "day", "part", "answer", "time", ""], &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Solution;
+
+    struct Panicking;
+
+    impl Solution for Panicking {
+        type Input = Vec<String>;
+
+        fn parse(&self, lines: &[String], _context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+            Ok(lines.to_vec())
+        }
+
+        fn part_one(&self, lines: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+            Ok(Answer::from(lines.len()))
+        }
+
+        fn part_two(&self, _lines: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+            panic!("out of range")
+        }
+    }
+
+    #[test]
+    fn fails_the_parts_whose_solver_panics() {
+        let mut registry = Registry::default();
+        registry.register("day_thirteen", Panicking);
+        let puzzle = &registry.puzzles()[0];
+        let input = InputSource::Text(String::from("0: 3"));
+        for &parallel in &[false, true] {
+            let runs = run_jobs(&[Part::One, Part::Two], parallel, |&part| run_part(puzzle, part, &input, &RunOptions::default()));
+            let outcomes: Vec<String> = runs.iter().map(|run| run.outcome.to_string()).collect();
+            assert_eq!(outcomes, vec!["1", "error: the solver panicked: out of range"]);
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::download::find;
+use crate::input::InputSource;
+use crate::json::Json;
+use crate::registry::Registry;
+use crate::runner::{get_panic_message, run_part, Outcome, RunOptions};
+use crate::solution::Part;
+use crate::trace::trace;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 1 << 20;
+
+pub const DEFAULT_SOLVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const DEFAULT_MAX_CONNECTIONS: usize = 32;
+
+const MAX_HEAD_SIZE: usize = 8192;
+
+/// How long a client may take to send its whole request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long past its timeout a part is waited for before the server stops waiting for it.
+const SOLVE_GRACE: Duration = Duration::from_millis(500);
+
+/// `max_input_size` is the largest body accepted, in bytes, `timeout` how long a part may take
+/// before it is stopped, `request_timeout` how long a client may take to send its request, and
+/// `max_connections` how many connections are served at once; the ones over it get a 503.
+pub struct ServeOptions {
+    pub strict: bool,
+    pub max_input_size: usize,
+    pub timeout: Duration,
+    pub request_timeout: Duration,
+    pub max_connections: usize
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        ServeOptions {
+            strict: false,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            timeout: DEFAULT_SOLVE_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_connections: DEFAULT_MAX_CONNECTIONS
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Json
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Response { status, body: Json::object(vec![("error", Json::from(message))]) }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error"
+        };
+        let body = self.body.to_string();
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status, reason, body.len(), body
+        ).into_bytes()
+    }
+}
+
+/// A connection that stops reading once its deadline passed, so that a client sending its request
+/// a byte at a time can't hold the server up for longer than a client sending nothing.
+struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining == Duration::from_secs(0) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the request took too long"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buffer)
+    }
+}
+
+impl Write for DeadlineStream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.stream.write(buffer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+fn get_read_error(error: io::Error) -> Response {
+    match error.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Response::error(408, "the request took too long"),
+        io::ErrorKind::UnexpectedEof => Response::error(400, "the request ended early"),
+        _ => Response::error(400, &error.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String
+}
+
+/// Reads one request, refusing heads and bodies over the limits before reading them whole, and
+/// bodies without a `Content-Length`.
+pub fn read_request<S: Read + Write>(stream: &mut S, max_input_size: usize) -> Result<Request, Response> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut buffer = [0; 4096];
+    let end = loop {
+        if let Some(end) = find(&bytes, b"\r\n\r\n") {
+            break end;
+        }
+        if bytes.len() > MAX_HEAD_SIZE {
+            return Err(Response::error(431, "the request head is too large"));
+        }
+        let count = stream.read(&mut buffer).map_err(get_read_error)?;
+        if count == 0 {
+            return Err(Response::error(400, "the request ended early"));
+        }
+        bytes.extend_from_slice(&buffer[..count]);
+    };
+    let head = String::from_utf8_lossy(&bytes[..end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let fields: Vec<&str> = lines.next().unwrap_or("").split(' ').collect();
+    let (method, path) = match fields.as_slice() {
+        [method, path, version] if version.starts_with("HTTP/") => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "malformed request line"))
+    };
+    let mut length: Option<usize> = None;
+    let mut expects_continue = false;
+    for line in lines {
+        let (name, value) = line.split_once(':').unwrap_or((line, ""));
+        match name.trim().to_lowercase().as_str() {
+            "content-length" => {
+                length = Some(value.trim().parse::<usize>().map_err(|_| Response::error(400, "invalid Content-Length"))?);
+            },
+            "transfer-encoding" => return Err(Response::error(411, "send the input with a Content-Length")),
+            "expect" => expects_continue = value.trim().eq_ignore_ascii_case("100-continue"),
+            _ => {}
+        }
+    }
+    let length = match (length, method.as_str()) {
+        (Some(length), _) => length,
+        (None, "POST") => return Err(Response::error(411, "send the input with a Content-Length")),
+        (None, _) => 0
+    };
+    if length > max_input_size {
+        return Err(Response::error(413, &format!("inputs are limited to {} bytes", max_input_size)));
+    }
+    if expects_continue {
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").map_err(|error| Response::error(400, &error.to_string()))?;
+    }
+    let mut body = bytes[end + 4..].to_vec();
+    body.truncate(length);
+    if body.len() < length {
+        let mut rest = vec![0; length - body.len()];
+        stream.read_exact(&mut rest).map_err(get_read_error)?;
+        body.extend_from_slice(&rest);
+    }
+    let body = String::from_utf8(body).map_err(|_| Response::error(400, "the input isn't UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+fn parse_solve_path(path: &str) -> Option<(u32, u32, Part)> {
+    let fields: Vec<&str> = path.strip_prefix("/solve/")?.split('/').collect();
+    match fields.as_slice() {
+        [year, day, part] => Some((year.parse().ok()?, day.parse().ok()?, Part::from_number(part.parse().ok()?)?)),
+        _ => None
+    }
+}
+
+/// Answers `POST /solve/<year>/<day>/<part>` with the run as JSON, the same object `--format json`
+/// writes; inputs that don't parse give 422 and parts that time out 503.
+pub fn handle(registry: &Registry, options: &ServeOptions, request: &Request) -> Response {
+    let (year, day, part) = match parse_solve_path(&request.path) {
+        Some(puzzle) => puzzle,
+        None => return Response::error(404, "expected /solve/<year>/<day>/<part>")
+    };
+    if request.method != "POST" {
+        return Response::error(405, "post the input to solve it");
+    }
+    let puzzle = match registry.find(year, day) {
+        Some(puzzle) => puzzle,
+        None => return Response::error(404, &format!("no puzzle for year {}, day {}", year, day))
+    };
+    let run_options = RunOptions { strict: options.strict, timeout: Some(options.timeout), ..RunOptions::default() };
+    let run = run_part(puzzle, part, &InputSource::Text(request.body.clone()), &run_options);
+    let status = match run.outcome {
+        Outcome::Failed(_) => 422,
+        Outcome::Interrupted(_) => 503,
+        Outcome::Solved(_) | Outcome::Unsolved => 200
+    };
+    Response { status, body: run.to_json() }
+}
+
+/// Handles the request on its own thread and waits for it until a little after the part's
+/// timeout, answering 503 for parts that don't stop by then; the thread is left to finish alone.
+/// Solvers that panic fail their part in `run_part`, which answers 422; anything else that panics
+/// answers 500.
+fn handle_in_time(shared: &Arc<(Registry, ServeOptions)>, request: Request) -> Response {
+    let (sender, receiver) = mpsc::channel();
+    let worker = Arc::clone(shared);
+    thread::spawn(move || {
+        let response = panic::catch_unwind(AssertUnwindSafe(|| handle(&worker.0, &worker.1, &request)))
+            .unwrap_or_else(|payload| {
+                Response::error(500, &format!("the server panicked: {}", get_panic_message(payload.as_ref())))
+            });
+        let _ = sender.send(response);
+    });
+    match receiver.recv_timeout(shared.1.timeout + SOLVE_GRACE) {
+        Ok(response) => response,
+        Err(RecvTimeoutError::Timeout) => Response::error(503, "the part didn't stop in time"),
+        Err(RecvTimeoutError::Disconnected) => Response::error(500, "the server stopped handling the request")
+    }
+}
+
+fn handle_connection(stream: TcpStream, shared: &Arc<(Registry, ServeOptions)>) {
+    let options = &shared.1;
+    let mut stream = DeadlineStream { stream, deadline: Instant::now() + options.request_timeout };
+    let response = match read_request(&mut stream, options.max_input_size) {
+        Ok(request) => {
+            let (method, path) = (request.method.clone(), request.path.clone());
+            let response = handle_in_time(shared, request);
+            trace!(Info, "{} {} {}", method, path, response.status);
+            response
+        },
+        Err(response) => {
+            trace!(Info, "rejected request: {}", response.status);
+            response
+        }
+    };
+    let _ = stream.write_all(&response.to_bytes());
+}
+
+/// Counts a connection as open until it is dropped, even when its thread panics.
+struct OpenConnection(Arc<AtomicUsize>);
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Answers requests until the process is stopped, each on its own thread so that a slow part
+/// doesn't hold up the others, up to `max_connections` at once.
+pub fn serve(listener: TcpListener, registry: Registry, options: ServeOptions) {
+    let shared = Arc::new((registry, options));
+    let num_open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let is_full = num_open.fetch_add(1, Ordering::SeqCst) >= shared.1.max_connections;
+                let open = OpenConnection(Arc::clone(&num_open));
+                if is_full {
+                    drop(open);
+                    trace!(Info, "rejected a connection: {} are open", shared.1.max_connections);
+                    let _ = stream.write_all(&Response::error(503, "too many connections; try again later").to_bytes());
+                    continue;
+                }
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let _open = open;
+                    handle_connection(stream, &shared);
+                });
+            },
+            Err(error) => trace!(Info, "cannot accept a connection: {}", error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::control::Control;
+    use crate::error::{ParseError, SolveError};
+    use crate::solution::{Answer, Solution};
+    use crate::utils::ParseContext;
+    #[cfg(feature = "year2017")]
+    use crate::registry::get_registry;
+
+    /// A stream reading the request and keeping what is written back.
+    struct Connection {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>
+    }
+
+    impl Read for Connection {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buffer)
+        }
+    }
+
+    impl Write for Connection {
+        fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buffer)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn get_request(text: &str) -> Request {
+        Request { method: String::from("POST"), path: String::from("/solve/2017/13/1"), body: text.to_string() }
+    }
+
+    #[cfg(feature = "year2017")]
+    fn get_field<'a>(response: &'a Response, key: &str) -> &'a Json {
+        match &response.body {
+            Json::Object(entries) => &entries.iter().find(|(name, _)| name == key).unwrap().1,
+            _ => panic!("not an object")
+        }
+    }
+
+    #[test]
+    fn reads_requests_within_the_limits() {
+        let mut connection = Connection {
+            input: Cursor::new(b"POST /solve/2017/13/1 HTTP/1.1\r\nContent-Length: 4\r\nExpect: 100-continue\r\n\r\n0: 3".to_vec()),
+            output: Vec::new()
+        };
+        assert_eq!(read_request(&mut connection, 4), Ok(get_request("0: 3")));
+        assert_eq!(connection.output, b"HTTP/1.1 100 Continue\r\n\r\n");
+        let mut connection = Connection {
+            input: Cursor::new(b"POST /solve/2017/13/1 HTTP/1.1\r\nContent-Length: 5\r\n\r\n0: 3\n".to_vec()),
+            output: Vec::new()
+        };
+        assert_eq!(read_request(&mut connection, 4).unwrap_err().status, 413);
+        let mut connection = Connection { input: Cursor::new(b"POST / HTTP/1.1\r\n\r\n".to_vec()), output: Vec::new() };
+        assert_eq!(read_request(&mut connection, 4).unwrap_err().status, 411);
+    }
+
+    #[cfg(feature = "year2017")]
+    #[test]
+    fn solves_posted_inputs() {
+        let registry = get_registry();
+        let options = ServeOptions { strict: true, ..ServeOptions::default() };
+        let response = handle(&registry, &options, &get_request("0: 3\n1: 2\n4: 4\n6: 4\n"));
+        assert_eq!(response.status, 200);
+        assert_eq!(get_field(&response, "answer"), &Json::Integer(24));
+        assert_eq!(handle(&registry, &options, &get_request("0: 3\n1 2\n")).status, 422);
+        let mut request = get_request("");
+        request.path = String::from("/solve/2017/13/3");
+        assert_eq!(handle(&registry, &options, &request).status, 404);
+        request.path = String::from("/solve/2015/1/1");
+        assert_eq!(handle(&registry, &options, &request).status, 404);
+        request.method = String::from("GET");
+        request.path = String::from("/solve/2017/13/1");
+        assert_eq!(handle(&registry, &options, &request).status, 405);
+    }
+
+    #[cfg(feature = "year2017")]
+    #[test]
+    fn stops_parts_that_run_too_long() {
+        let options = ServeOptions { timeout: Duration::from_secs(0), ..ServeOptions::default() };
+        let mut request = get_request("..#\n#..\n...\n");
+        request.path = String::from("/solve/2017/22/2");
+        let response = handle(&get_registry(), &options, &request);
+        assert_eq!(response.status, 503);
+        assert!(matches!(get_field(&response, "progress"), Json::Integer(_)));
+    }
+
+    #[cfg(feature = "year2017")]
+    #[test]
+    fn answers_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, get_registry(), ServeOptions::default()));
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"POST /solve/2017/13/2 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 20\r\n\r\n0: 3\n1: 2\n4: 4\n6: 4\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("\"answer\":10"), "{}", response);
+    }
+
+    fn start(registry: Registry, options: ServeOptions) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, registry, options));
+        address
+    }
+
+    /// A solver that never looks at its control.
+    struct Stuck;
+
+    impl Solution for Stuck {
+        type Input = Vec<String>;
+
+        fn parse(&self, lines: &[String], _context: &mut ParseContext) -> Result<Self::Input, ParseError> {
+            Ok(lines.to_vec())
+        }
+
+        fn part_one(&self, _lines: &Self::Input, _control: &Control) -> Result<Answer, SolveError> {
+            thread::sleep(Duration::from_secs(10));
+            Ok(Answer::from(0u64))
+        }
+    }
+
+    #[test]
+    fn stops_waiting_for_parts_that_ignore_the_timeout() {
+        let mut registry = Registry::default();
+        registry.register("day_thirteen", Stuck);
+        let address = start(registry, ServeOptions { timeout: Duration::from_millis(100), ..ServeOptions::default() });
+        let mut stream = TcpStream::connect(address).unwrap();
+        let start = Instant::now();
+        stream.write_all(b"POST /solve/2017/13/1 HTTP/1.1\r\nContent-Length: 4\r\n\r\n0: 3").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", response);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn times_out_requests_sent_too_slowly() {
+        let address = start(Registry::default(), ServeOptions { request_timeout: Duration::from_millis(300), ..ServeOptions::default() });
+        let mut stream = TcpStream::connect(address).unwrap();
+        let start = Instant::now();
+        for &byte in b"POST /solve/2017/13/1 HTTP/1.1\r\n".iter() {
+            if stream.write_all(&[byte]).is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", response);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn turns_away_connections_over_the_limit() {
+        let address = start(Registry::default(), ServeOptions { max_connections: 1, ..ServeOptions::default() });
+        let _open = TcpStream::connect(address).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", response);
+    }
+}
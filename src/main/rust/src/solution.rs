@@ -0,0 +1,205 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use crate::control::Control;
+use crate::error::{ParseError, SolveError};
+use crate::parameters::Parameters;
+use crate::random::Random;
+use crate::utils::ParseContext;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i64),
+    Text(String)
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(formatter, "{}", value),
+            Answer::Text(text) => write!(formatter, "{}", text)
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Answer::Integer(value)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Answer::Integer(value as i64)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Integer(value as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two
+}
+
+impl Part {
+    pub fn from_number(number: u32) -> Option<Self> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None
+        }
+    }
+
+    pub fn number(self) -> u32 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.number())
+    }
+}
+
+/// A puzzle solver: parses the input lines once and answers each part from the parsed input.
+/// Solvers with long loops check the control on every step and stop when it says so.
+pub trait Solution {
+    type Input;
+
+    fn parse(&self, lines: &[String], context: &mut ParseContext) -> Result<Self::Input, ParseError>;
+
+    fn part_one(&self, input: &Self::Input, control: &Control) -> Result<Answer, SolveError>;
+
+    /// Puzzles whose second part isn't solved yet keep the default.
+    fn part_two(&self, _input: &Self::Input, _control: &Control) -> Result<Option<Answer>, SolveError> {
+        Ok(None)
+    }
+
+    /// Makes up an input in the format `parse` accepts, for stress testing the solver.
+    fn generate(&self, _random: &mut Random) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Takes the parameters the catalogue lists for the puzzle; solvers without any keep the default.
+    fn configure(&mut self, _parameters: &Parameters) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// An answer along with the time spent parsing the input and solving the part.
+pub struct Timed {
+    pub answer: Option<Answer>,
+    pub parse: Duration,
+    pub solve: Duration
+}
+
+/// Object safe view of a `Solution`, used by the registry to store solvers of different inputs.
+pub trait Solver {
+    fn solve(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Option<Answer>, SolveError>;
+
+    fn solve_timed(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Timed, SolveError>;
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>>;
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String>;
+}
+
+impl <S: Solution> Solver for S {
+    fn solve(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Option<Answer>, SolveError> {
+        let input = self.parse(lines, context)?;
+        let answer = match part {
+            Part::One => Some(self.part_one(&input, control)?),
+            Part::Two => self.part_two(&input, control)?
+        };
+        Ok(answer)
+    }
+
+    fn solve_timed(
+        &self,
+        lines: &[String],
+        part: Part,
+        context: &mut ParseContext,
+        control: &Control
+    ) -> Result<Timed, SolveError> {
+        let start = Instant::now();
+        let input = self.parse(lines, context)?;
+        let parse = start.elapsed();
+        let start = Instant::now();
+        let answer = match part {
+            Part::One => Some(self.part_one(&input, control)?),
+            Part::Two => self.part_two(&input, control)?
+        };
+        Ok(Timed { answer, parse, solve: start.elapsed() })
+    }
+
+    fn generate(&self, random: &mut Random) -> Option<Vec<String>> {
+        Solution::generate(self, random)
+    }
+
+    fn configure(&mut self, parameters: &Parameters) -> Result<(), String> {
+        Solution::configure(self, parameters)
+    }
+}
+
+/// Solves one part of a puzzle example given as lines, parsing it strictly so that a typo in the
+/// example fails the test instead of being skipped.
+#[cfg(test)]
+pub fn solve_example<S: Solution>(solution: &S, lines: &[&str], part: Part) -> Option<Answer> {
+    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    let mut context = ParseContext::new(None, true);
+    solution.solve(&lines, part, &mut context, &Control::new()).unwrap()
+}
+
+/// The error strict parsing stops a puzzle example at.
+#[cfg(test)]
+pub fn get_example_parse_error<S: Solution>(solution: &S, lines: &[&str]) -> ParseError {
+    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    let mut context = ParseContext::new(None, true);
+    match solution.parse(&lines, &mut context) {
+        Ok(_) => panic!("the example parses"),
+        Err(error) => error
+    }
+}
+
+/// Checks that the inputs made up by the generator of a puzzle parse strictly, for a few seeds.
+#[cfg(test)]
+pub fn assert_generated_inputs_parse<S: Solution>(solution: &S) {
+    for seed in 0..20 {
+        let lines = Solution::generate(solution, &mut Random::new(seed)).expect("the puzzle has no generator");
+        let mut context = ParseContext::new(None, true);
+        if let Err(error) = solution.parse(&lines, &mut context) {
+            panic!("input generated with seed {} doesn't parse: {}", seed, error);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Environment variable holding the trace level when `--trace` isn't given.
+pub const TRACE_VARIABLE: &str = "AOC_TRACE";
+
+/// How much the solvers tell about what they do: `Info` for what they found, `Debug` for their key
+/// decisions and `Trace` for every step of their main loops.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Info,
+    Debug,
+    Trace
+}
+
+impl Level {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Level::Off,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace"
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.to_lowercase().as_str() {
+            "off" | "" => Ok(Level::Off),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("unknown trace level '{}'; expected off, info, debug or trace", string))
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn get_level() -> Level {
+    Level::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+pub fn is_enabled(level: Level) -> bool {
+    level != Level::Off && level <= get_level()
+}
+
+/// The level set in the environment, if any.
+pub fn get_env_level() -> Result<Option<Level>, String> {
+    match env::var(TRACE_VARIABLE) {
+        Ok(value) => value.parse::<Level>().map(Some),
+        Err(_) => Ok(None)
+    }
+}
+
+/// Writes a trace line to stderr, so that traces never mix with the answers on stdout.
+pub fn write(level: Level, module: &str, arguments: fmt::Arguments) {
+    let module = module.rsplit("::").next().unwrap_or(module);
+    eprintln!("[{} {}] {}", level.name(), module, arguments);
+}
+
+/// Writes a formatted trace line when the level is enabled; the arguments aren't evaluated
+/// otherwise, so trace points cost one atomic load when tracing is off:
+///
+/// ```ignore
+/// trace!(Debug, "bot {} gives {} to {:?}", bot, value, output);
+/// ```
+#[allow(unused_macros)]
+macro_rules! trace {
+    ($level:ident, $($arguments:tt)+) => {
+        if $crate::trace::is_enabled($crate::trace::Level::$level) {
+            $crate::trace::write($crate::trace::Level::$level, module_path!(), format_args!($($arguments)+));
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use trace;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_levels() {
+        assert_eq!("debug".parse::<Level>(), Ok(Level::Debug));
+        assert_eq!("TRACE".parse::<Level>(), Ok(Level::Trace));
+        assert!("loud".parse::<Level>().is_err());
+    }
+
+    #[test]
+    fn enables_levels_up_to_the_one_set() {
+        set_level(Level::Info);
+        assert!(is_enabled(Level::Info));
+        assert!(!is_enabled(Level::Debug));
+        assert!(!is_enabled(Level::Off));
+        set_level(Level::Off);
+        assert!(!is_enabled(Level::Info));
+    }
+}
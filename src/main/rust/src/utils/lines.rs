@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use crate::error::Error;
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// How lines are read besides always dropping `\n`, `\r\n` and stray `\r` endings and a byte order
+/// mark at the start: `trim` strips the whitespace around each line and `skip_blank` leaves out the lines
+/// that are blank.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineOptions {
+    pub trim: bool,
+    pub skip_blank: bool
+}
+
+/// A line with its number in the input, counted from 1 and counting the lines that were skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub number: usize,
+    pub text: String
+}
+
+/// Reads lines one at a time as they are asked for, so that large files and stdin don't have to
+/// be loaded whole. A file that doesn't end with a newline has the same lines as one that does.
+pub struct Lines<R: BufRead> {
+    reader: R,
+    source: String,
+    options: LineOptions,
+    number: usize
+}
+
+impl<R: BufRead> Lines<R> {
+    /// `source` names the input in errors.
+    pub fn new(reader: R, source: &str, options: LineOptions) -> Self {
+        Lines { reader, source: source.to_string(), options, number: 0 }
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, Error> {
+        let mut text = String::new();
+        let count = self.reader.read_line(&mut text)
+            .map_err(|source| Error::Io { path: self.source.clone(), source })?;
+        if count == 0 {
+            return Ok(None);
+        }
+        self.number += 1;
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        if text.ends_with('\r') {
+            text.pop();
+        }
+        if self.number == 1 && text.starts_with(BYTE_ORDER_MARK) {
+            text.remove(0);
+        }
+        Ok(Some(text))
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = Result<Line, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let text = match self.read_line() {
+                Ok(Some(text)) => text,
+                Ok(None) => return None,
+                Err(error) => return Some(Err(error))
+            };
+            let text = if self.options.trim { text.trim().to_string() } else { text };
+            if self.options.skip_blank && text.trim().is_empty() {
+                continue;
+            }
+            return Some(Ok(Line { number: self.number, text }));
+        }
+    }
+}
+
+pub fn open_lines(path: &str, options: LineOptions) -> Result<Lines<BufReader<File>>, Error> {
+    let file = File::open(path).map_err(|source| Error::Io { path: path.to_string(), source })?;
+    Ok(Lines::new(BufReader::new(file), path, options))
+}
+
+pub fn stdin_lines(options: LineOptions) -> Lines<io::StdinLock<'static>> {
+    Lines::new(io::stdin().lock(), "<stdin>", options)
+}
+
+pub fn string_lines(string: &str, options: LineOptions) -> Lines<&[u8]> {
+    Lines::new(string.as_bytes(), "<input>", options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_texts(string: &str, options: LineOptions) -> Vec<String> {
+        string_lines(string, options).map(|line| line.unwrap().text).collect()
+    }
+
+    #[test]
+    fn normalizes_line_endings_and_byte_order_marks() {
+        let options = LineOptions::default();
+        assert_eq!(get_texts("\u{feff}a\r\nb\n\r\nc", options), vec!["a", "b", "", "c"]);
+        assert_eq!(get_texts("a\nb\n", options), vec!["a", "b"]);
+        assert_eq!(get_texts("a\r\nb\r", options), vec!["a", "b"]);
+        assert!(get_texts("", options).is_empty());
+    }
+
+    #[test]
+    fn trims_and_skips_blank_lines_keeping_line_numbers() {
+        let options = LineOptions { trim: true, skip_blank: true };
+        let lines: Vec<Line> = string_lines(" a \r\n\r\n  \nb", options).map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec![Line { number: 1, text: String::from("a") }, Line { number: 4, text: String::from("b") }]);
+    }
+
+    #[test]
+    fn reports_where_invalid_text_is() {
+        let mut lines = Lines::new(&[b'a', b'\n', 0xff, b'\n'][..], "input.txt", LineOptions::default());
+        assert_eq!(lines.next().unwrap().unwrap().text, "a");
+        assert!(lines.next().unwrap().unwrap_err().to_string().starts_with("cannot read 'input.txt'"));
+    }
+}
@@ -0,0 +1,171 @@
+use std::cmp::max;
+use crate::error::{Error, ParseError};
+use self::lines::{open_lines, string_lines, LineOptions};
+use self::pattern::PatternError;
+
+pub mod lines;
+pub mod pattern;
+
+pub fn read_lines(path: &str) -> Result<Vec<String>, Error> {
+    open_lines(path, LineOptions::default())?
+        .map(|line| line.map(|line| line.text))
+        .collect()
+}
+
+pub fn get_lines(string: &str) -> Vec<String> {
+    string_lines(string, LineOptions::default())
+        .filter_map(|line| line.ok())
+        .map(|line| line.text)
+        .collect()
+}
+
+/// What a line parser gives back: nothing when the line doesn't parse, or an error telling why.
+pub trait ParsedLine<T> {
+    fn into_result(self) -> Result<T, Option<String>>;
+}
+
+impl<T> ParsedLine<T> for Option<T> {
+    fn into_result(self) -> Result<T, Option<String>> {
+        self.ok_or(None)
+    }
+}
+
+impl<T> ParsedLine<T> for Result<T, String> {
+    fn into_result(self) -> Result<T, Option<String>> {
+        self.map_err(Some)
+    }
+}
+
+impl<T> ParsedLine<T> for Result<T, PatternError> {
+    fn into_result(self) -> Result<T, Option<String>> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(PatternError::NoMatch { .. }) => Err(None),
+            Err(error) => Err(Some(error.to_string()))
+        }
+    }
+}
+
+/// Decides what happens to input lines that don't parse: strict parsing stops at the first one,
+/// lenient parsing skips it and keeps it as a diagnostic.
+pub struct ParseContext {
+    pub file: Option<String>,
+    pub strict: bool,
+    pub skipped: Vec<ParseError>
+}
+
+impl ParseContext {
+    pub fn new(file: Option<&str>, strict: bool) -> Self {
+        ParseContext { file: file.map(|file| file.to_string()), strict, skipped: Vec::new() }
+    }
+
+    pub fn error(&self, line: usize, text: &str, expected: &'static str) -> ParseError {
+        ParseError::new(self.file.as_deref(), line, text, expected)
+    }
+
+    pub fn reject(&mut self, line: usize, text: &str, expected: &'static str) -> Result<(), ParseError> {
+        let error = self.error(line, text, expected);
+        self.skip(error)
+    }
+
+    fn skip(&mut self, error: ParseError) -> Result<(), ParseError> {
+        if self.strict {
+            Err(error)
+        } else {
+            self.skipped.push(error);
+            Ok(())
+        }
+    }
+
+    /// Gives back the value of a line that parsed, or rejects the line with the parser's reason.
+    pub fn accept<T, R: ParsedLine<T>>(
+        &mut self,
+        line: usize,
+        text: &str,
+        expected: &'static str,
+        parsed: R
+    ) -> Result<Option<T>, ParseError> {
+        match parsed.into_result() {
+            Ok(value) => Ok(Some(value)),
+            Err(detail) => {
+                let error = self.error(line, text, expected).with_detail(detail);
+                self.skip(error)?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn parse_lines<T, R: ParsedLine<T>, F: Fn(&str) -> R>(
+        &mut self,
+        lines: &[String],
+        expected: &'static str,
+        parse: F
+    ) -> Result<Vec<T>, ParseError> {
+        let mut result: Vec<T> = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(value) = self.accept(index + 1, line, expected, parse(line))? {
+                result.push(value);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn parse_first<T, R: ParsedLine<T>, F: Fn(&str) -> R>(
+        &self,
+        lines: &[String],
+        expected: &'static str,
+        parse: F
+    ) -> Result<T, ParseError> {
+        let (index, line) = lines.iter()
+            .enumerate()
+            .find(|(_, line)| !line.trim().is_empty())
+            .ok_or_else(|| self.error(1, "", expected))?;
+        parse(line.trim()).into_result()
+            .map_err(|detail| self.error(index + 1, line, expected).with_detail(detail))
+    }
+}
+
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = max(widths[index], cell.chars().count());
+        }
+    }
+    let header: Vec<String> = header.iter().map(|title| title.to_string()).collect();
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    let mut lines: Vec<String> = vec![format_row(&header, &widths), format_row(&separator, &widths)];
+    for row in rows {
+        lines.push(format_row(row, &widths));
+    }
+    lines.join("\n")
+}
+
+fn format_row(cells: &[String], widths: &[usize]) -> String {
+    let row = cells.iter()
+        .zip(widths.iter())
+        .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    row.trim_end().to_string()
+}
+
+pub fn remove<T:Eq + PartialEq>(vec: &mut Vec<T>, element: T) {
+    if let Some(index) = vec.iter().position(|val| *val == element) {
+        vec.remove(index);
+    }
+}
+
+pub fn get_num_set_bits(n: u64) -> u8 {
+    let mut result: u8 = 0;
+    for shift in 0..64 {
+        let mask = 1u64 << shift;
+        if n & mask != 0 {
+            result += 1;
+        }
+    }
+    result
+}
\ No newline at end of file
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::str::FromStr;
+use regex::{Captures, Regex};
+
+pub use lazy_static::lazy_static;
+
+/// A regular expression for a whole input line whose capture groups are the named fields of what
+/// the line describes. Declared with `parse_line!` rather than built by hand.
+pub struct Pattern {
+    source: &'static str,
+    regex: Regex,
+    names: &'static [&'static str]
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    NoMatch { pattern: &'static str },
+    Field { name: &'static str, text: String, type_name: &'static str }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::NoMatch { pattern } => write!(formatter, "no match for /{}/", pattern),
+            PatternError::Field { name, text, type_name } => {
+                write!(formatter, "'{}' is not a valid {} for {}", text, type_name, name)
+            }
+        }
+    }
+}
+
+/// Reads the fields of a matched line in order, parsing each one into the type asked for.
+pub struct Fields<'t> {
+    captures: Captures<'t>,
+    names: &'static [&'static str],
+    index: usize
+}
+
+/// The type's own name, without the module path `std::any::type_name` gives it.
+fn get_short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let end = name.find('<').unwrap_or(name.len());
+    name[..end].rsplit("::").next().unwrap_or(name)
+}
+
+impl Pattern {
+    /// Panics when the number of capture groups isn't the number of fields, which is a mistake in
+    /// the declaration rather than in the input.
+    pub fn new(source: &'static str, names: &'static [&'static str]) -> Self {
+        let regex = Regex::new(source).unwrap();
+        assert_eq!(regex.captures_len() - 1, names.len(), "pattern /{}/ doesn't have one group per field", source);
+        Pattern { source, regex, names }
+    }
+
+    pub fn fields<'t>(&self, string: &'t str) -> Result<Fields<'t>, PatternError> {
+        let captures = self.regex.captures(string)
+            .ok_or(PatternError::NoMatch { pattern: self.source })?;
+        Ok(Fields { captures, names: self.names, index: 0 })
+    }
+}
+
+impl<'t> Fields<'t> {
+    /// A group that didn't take part in the match reads as an empty string.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T: FromStr>(&mut self) -> Result<T, PatternError> {
+        let name = self.names[self.index];
+        self.index += 1;
+        let text = self.captures.get(self.index).map_or("", |re_match| re_match.as_str());
+        text.parse::<T>()
+            .map_err(|_| PatternError::Field { name, text: text.to_string(), type_name: get_short_type_name::<T>() })
+    }
+}
+
+/// A field holding a comma separated list, such as `2, 3, 5`.
+pub struct List<T>(pub Vec<T>);
+
+impl<T: FromStr> FromStr for List<T> {
+    type Err = T::Err;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        string.split(',')
+            .map(|item| item.trim().parse::<T>())
+            .collect::<Result<Vec<T>, T::Err>>()
+            .map(List)
+    }
+}
+
+/// Matches a line against a pattern compiled once for the call site and builds a value from its
+/// typed fields, one per capture group, either with an expression over the fields or straight
+/// into a struct or enum variant with fields of the same names. Braces hold alternatives, tried in
+/// order until one matches:
+///
+/// ```ignore
+/// parse_line!(string, r"(\d+): (\d+)" => Scanner { layer: u64, depth: u64 })
+/// parse_line!(string, r"(\d+) <-> (.+)" => |id: u64, neighbours: List<u64>| Node { id, neighbours_ids: neighbours.0 })
+/// parse_line!(string, {
+///     r"s(\d+)" => |length: usize| DanceMove::Spin { length },
+///     r"x(\d+)/(\d+)" => |first: usize, second: usize| DanceMove::Exchange { first, second }
+/// })
+/// ```
+///
+/// Evaluates to a `Result` whose `PatternError` tells which field didn't parse. Unused when the
+/// crate is built without any puzzle set.
+#[allow(unused_macros)]
+macro_rules! parse_line {
+    ($string:expr, {
+        $first:literal => |$($first_field:ident: $first_type:ty),+| $first_body:expr
+        $(, $pattern:literal => |$($field:ident: $type:ty),+| $body:expr)* $(,)?
+    }) => {{
+        let string = $string;
+        let mut result = $crate::utils::pattern::parse_line!(string, $first => |$($first_field: $first_type),+| $first_body);
+        $(
+            if let Err($crate::utils::pattern::PatternError::NoMatch { .. }) = result {
+                result = $crate::utils::pattern::parse_line!(string, $pattern => |$($field: $type),+| $body);
+            }
+        )*
+        result.map_err(|error| match error {
+            $crate::utils::pattern::PatternError::NoMatch { .. } => {
+                $crate::utils::pattern::PatternError::NoMatch { pattern: concat!($first $(, "|", $pattern)*) }
+            },
+            error => error
+        })
+    }};
+    ($string:expr, $pattern:literal => |$($field:ident: $type:ty),+| $body:expr) => {{
+        $crate::utils::pattern::lazy_static! {
+            static ref PATTERN: $crate::utils::pattern::Pattern =
+                $crate::utils::pattern::Pattern::new($pattern, &[$(stringify!($field)),+]);
+        }
+        PATTERN.fields($string).and_then(|mut fields| {
+            $(let $field: $type = fields.next()?;)+
+            Ok($body)
+        })
+    }};
+    ($string:expr, $pattern:literal => $($name:ident)::+ { $($field:ident: $type:ty),+ $(,)? }) => {
+        $crate::utils::pattern::parse_line!($string, $pattern => |$($field: $type),+| $($name)::+ { $($field),+ })
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use parse_line;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Line {
+        Range { start: u64, end: u64 },
+        Total(i64)
+    }
+
+    fn parse_range(string: &str) -> Result<Line, PatternError> {
+        parse_line!(string, r"^(\d+)-(\d+)$" => Line::Range { start: u64, end: u64 })
+    }
+
+    #[test]
+    fn builds_values_from_typed_fields() {
+        assert_eq!(parse_range("3-14"), Ok(Line::Range { start: 3, end: 14 }));
+        let total = parse_line!("total of 1, -2, 3", r"total of (.+)" => |values: List<i64>| Line::Total(values.0.iter().sum()));
+        assert_eq!(total, Ok(Line::Total(2)));
+    }
+
+    #[test]
+    fn tries_alternatives_in_order() {
+        let parse = |string: &str| parse_line!(string, {
+            r"^(\d+)-(\d+)$" => |start: u64, end: u64| Line::Range { start, end },
+            r"^total (-?\d+)$" => |total: i64| Line::Total(total)
+        });
+        assert_eq!(parse("1-2"), Ok(Line::Range { start: 1, end: 2 }));
+        assert_eq!(parse("total -7"), Ok(Line::Total(-7)));
+        assert_eq!(parse("total"), Err(PatternError::NoMatch { pattern: r"^(\d+)-(\d+)$|^total (-?\d+)$" }));
+    }
+
+    #[test]
+    fn tells_which_field_does_not_parse() {
+        assert_eq!(parse_range("3 to 14"), Err(PatternError::NoMatch { pattern: r"^(\d+)-(\d+)$" }));
+        let error = parse_range("3-99999999999999999999").unwrap_err();
+        assert_eq!(error.to_string(), "'99999999999999999999' is not a valid u64 for end");
+        let error = parse_line!("1, x", r"(.+)" => |values: List<u8>| values.0).unwrap_err();
+        assert_eq!(error.to_string(), "'1, x' is not a valid List for values");
+    }
+}